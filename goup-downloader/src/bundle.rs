@@ -0,0 +1,363 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::anyhow;
+
+use goup_misc::manifest::compute_file_sha256;
+use goup_misc::op::{self, GoFile, GoRelease};
+use goup_misc::{Dir, progress};
+
+use crate::downloader::download_archive;
+use crate::utils;
+
+/// the trimmed release index in a bundle, newest first with `go` prefix like upstream.
+const BUNDLE_RELEASE_INDEX: &str = "releases.json";
+
+/// what a bundle contains.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BundleReport {
+    /// the versions, such as `1.21.9`.
+    pub versions: Vec<String>,
+    pub archives: usize,
+    /// total size of the archives.
+    pub bytes: u64,
+}
+
+/// create_bundle packs the archives of the versions for the platforms, their sha256 and
+/// a trimmed release index into a tar file, which `import_bundle` seeds an offline
+/// `GOUP_HOME` with. the platforms are `{os}/{arch}`, the current platform if empty.
+pub fn create_bundle(
+    versions: &[String],
+    platforms: &[String],
+    host: &str,
+    output: &Path,
+) -> anyhow::Result<BundleReport> {
+    let platforms = utils::parse_platforms(platforms)?;
+
+    let releases = op::list_upstream_go_releases(host)?;
+    let available: Vec<String> = releases.iter().map(|r| r.version.clone()).collect();
+    let mut selected: Vec<GoRelease> = Vec::new();
+    for ver in versions {
+        let version = op::match_version_req_with(ver, || Ok(available.clone()))?;
+        let release = releases
+            .iter()
+            .find(|r| r.version == version)
+            .ok_or_else(|| anyhow!("Go version {version} does not exist."))?;
+        if selected.iter().any(|r| r.version == release.version) {
+            continue;
+        }
+        let files: Vec<GoFile> = release
+            .files
+            .iter()
+            .filter(|f| {
+                f.kind == "archive"
+                    && platforms
+                        .iter()
+                        .any(|(os, arch)| *os == f.os && *arch == f.arch)
+            })
+            .cloned()
+            .collect();
+        for (os, arch) in &platforms {
+            if !files.iter().any(|f| f.os == *os && f.arch == *arch) {
+                anyhow::bail!("Go version {version} is not built for {os}/{arch}.");
+            }
+        }
+        selected.push(GoRelease {
+            version: release.version.clone(),
+            stable: release.stable,
+            files,
+        });
+    }
+
+    // the archives are downloaded into the cache, so they are reused.
+    let goup_home = Dir::goup_home()?;
+    fs::create_dir_all(goup_home.cache())?;
+    let mp = progress::multi_progress();
+    let sp = progress::spinner();
+    let mut tar = tar::Builder::new(File::create(output)?);
+    let mut report = BundleReport::default();
+    for file in selected.iter().flat_map(|r| &r.files) {
+        let archive = goup_home.cache_file(&file.filename);
+        if !archive.exists() || compute_file_sha256(&archive)? != file.sha256 {
+            let (archive_url, _) = utils::archive_url(&file.filename);
+            sp.set_message(format!("Downloading {archive_url}"));
+            download_archive(mp, &archive, &archive_url)?;
+            if compute_file_sha256(&archive)? != file.sha256 {
+                fs::remove_file(&archive)?;
+                anyhow::bail!("Hashsum NOT match {}", file.filename);
+            }
+            fs::write(
+                goup_home.cache_file(utils::archive_sha256(&file.filename)),
+                &file.sha256,
+            )?;
+        }
+        sp.set_message(format!("Packing {}", file.filename));
+        tar.append_path_with_name(&archive, &file.filename)?;
+        append_data(
+            &mut tar,
+            &utils::archive_sha256(&file.filename),
+            file.sha256.as_bytes(),
+        )?;
+        report.archives += 1;
+        report.bytes += fs::metadata(&archive)?.len();
+    }
+    report.versions = selected.iter().map(|r| r.version.clone()).collect();
+
+    let index: Vec<GoRelease> = selected
+        .into_iter()
+        .rev()
+        .map(|mut r| {
+            r.version = format!("go{}", r.version);
+            r
+        })
+        .collect();
+    append_data(&mut tar, BUNDLE_RELEASE_INDEX, &serde_json::to_vec(&index)?)?;
+    tar.into_inner()?.flush()?;
+    sp.finish_and_clear();
+    Ok(report)
+}
+
+/// import_bundle seeds the cache and the cached release index with a bundle made by
+/// `create_bundle`, so the bundled versions install offline. every archive is verified
+/// against its sha256 before it replaces the cached one.
+pub fn import_bundle(bundle: &Path) -> anyhow::Result<BundleReport> {
+    let goup_home = Dir::goup_home()?;
+    fs::create_dir_all(goup_home.cache())?;
+
+    // the archives are unpacked to temporary files, and verified once their sha256 are read.
+    let mut imported: Vec<(String, PathBuf)> = Vec::new();
+    let unpacked = unpack_bundle(&goup_home, bundle, &mut imported);
+    let (index, sidecars) = match unpacked {
+        Ok(v) => v,
+        Err(e) => {
+            for (_, tmp) in &imported {
+                let _ = fs::remove_file(tmp);
+            }
+            return Err(e);
+        }
+    };
+
+    let mut report = BundleReport::default();
+    let mut mismatched = Vec::new();
+    for (name, tmp) in imported {
+        let expected = sidecars.get(&name).cloned().or_else(|| {
+            index
+                .iter()
+                .flat_map(|r| &r.files)
+                .find(|f| f.filename == name)
+                .map(|f| f.sha256.clone())
+        });
+        let verified = match &expected {
+            Some(sha256) => compute_file_sha256(&tmp)?.eq_ignore_ascii_case(sha256),
+            None => false,
+        };
+        if !verified {
+            fs::remove_file(&tmp)?;
+            mismatched.push(name);
+            continue;
+        }
+        let sha256 = expected.unwrap_or_default();
+        report.archives += 1;
+        report.bytes += fs::metadata(&tmp)?.len();
+        fs::rename(&tmp, goup_home.cache_file(&name))?;
+        fs::write(goup_home.cache_file(utils::archive_sha256(&name)), sha256)?;
+    }
+    report.versions = index
+        .iter()
+        .rev()
+        .map(|r| r.version.trim_start_matches("go").to_owned())
+        .collect();
+    op::merge_release_index(index)?;
+    if !mismatched.is_empty() {
+        anyhow::bail!(
+            "Hashsum NOT match {}, the other archives are imported.",
+            mismatched.join(", ")
+        );
+    }
+    Ok(report)
+}
+
+/// unpack_bundle unpacks the archives of the bundle to temporary files in the cache, which
+/// are pushed to `imported`, and returns the release index and the sha256 of the archives.
+fn unpack_bundle(
+    goup_home: &Dir,
+    bundle: &Path,
+    imported: &mut Vec<(String, PathBuf)>,
+) -> anyhow::Result<(Vec<GoRelease>, HashMap<String, String>)> {
+    let mut index = None;
+    let mut sidecars = HashMap::new();
+    let mut tar = tar::Archive::new(File::open(bundle)?);
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        // only the plain file names are accepted, never a path out of the cache.
+        let Some(name) = path
+            .to_str()
+            .filter(|n| path.components().count() == 1 && !n.starts_with('.'))
+            .map(ToOwned::to_owned)
+        else {
+            anyhow::bail!("Invalid entry {} in the bundle.", path.display());
+        };
+        if name == BUNDLE_RELEASE_INDEX {
+            let mut content = String::new();
+            entry.read_to_string(&mut content)?;
+            index = Some(serde_json::from_str::<Vec<GoRelease>>(&content)?);
+            continue;
+        }
+        if !entry.header().entry_type().is_file() {
+            log::warn!("Skipped {name} in the bundle.");
+            continue;
+        }
+        if let Some(archive) = name.strip_suffix(".sha256") {
+            let mut sha256 = String::new();
+            entry.read_to_string(&mut sha256)?;
+            sidecars.insert(archive.to_owned(), sha256.trim().to_owned());
+        } else if name.ends_with(".tar.gz") || name.ends_with(".zip") {
+            let tmp = goup_home.cache_file(format!(".{name}.import"));
+            imported.push((name, tmp.to_path_buf()));
+            entry.unpack(&tmp)?;
+        } else {
+            log::warn!("Skipped {name} in the bundle.");
+        }
+    }
+    let index = index.ok_or_else(|| {
+        anyhow!(
+            "{} is not a goup bundle, {BUNDLE_RELEASE_INDEX} is missing.",
+            bundle.display()
+        )
+    })?;
+    Ok((index, sidecars))
+}
+
+/// append_data appends the data as a regular file.
+fn append_data<W: Write>(tar: &mut tar::Builder<W>, name: &str, data: &[u8]) -> anyhow::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default(),
+    );
+    tar.append_data(&mut header, name, data)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use goup_misc::consts::GOUP_HOME;
+
+    use super::*;
+    use crate::MirrorServer;
+
+    const ARCHIVE: &str = "go1.21.9.linux-amd64.tar.gz";
+
+    /// cache the archive of go1.21.9 and its release index, returns the archive sha256.
+    fn seed(goup_home: &Dir, content: &[u8]) -> anyhow::Result<String> {
+        fs::create_dir_all(goup_home.cache())?;
+        let archive = goup_home.cache_file(ARCHIVE);
+        fs::write(&archive, content)?;
+        let sha256 = compute_file_sha256(&archive)?;
+        let index = vec![GoRelease {
+            version: "go1.21.9".to_owned(),
+            stable: true,
+            files: vec![GoFile {
+                arch: "amd64".to_owned(),
+                filename: ARCHIVE.to_owned(),
+                kind: "archive".to_owned(),
+                os: "linux".to_owned(),
+                sha256: sha256.clone(),
+                size: content.len() as isize,
+                version: "go1.21.9".to_owned(),
+            }],
+        }];
+        fs::write(goup_home.release_index(), serde_json::to_vec(&index)?)?;
+        Ok(sha256)
+    }
+
+    /// the temporary files left in the cache.
+    fn leftovers(goup_home: &Dir) -> anyhow::Result<Vec<String>> {
+        Ok(fs::read_dir(goup_home.cache())?
+            .filter_map(|v| v.ok())
+            .map(|v| v.file_name().to_string_lossy().to_string())
+            .filter(|v| v.starts_with('.'))
+            .collect())
+    }
+
+    #[test]
+    fn test_bundle_round_trip() -> anyhow::Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        let source = Dir::new(tmp_dir.path().join("source"));
+        let target = Dir::new(tmp_dir.path().join("target"));
+        let bundle = tmp_dir.path().join("go-bundle.tar");
+        let sha256 = seed(&source, b"go1.21.9 archive")?;
+
+        let created = temp_env::with_var(GOUP_HOME, Some(source.as_os_str()), || {
+            // the archive is cached, the mirror only serves the release index.
+            let server = MirrorServer::bind("127.0.0.1:0", None)?;
+            let host = format!("http://{}", server.local_addr());
+            thread::spawn(move || server.run());
+            create_bundle(
+                &["1.21".to_owned()],
+                &["linux/amd64".to_owned()],
+                &host,
+                &bundle,
+            )
+        })?;
+        assert_eq!(
+            created,
+            BundleReport {
+                versions: vec!["1.21.9".to_owned()],
+                archives: 1,
+                bytes: 16,
+            }
+        );
+
+        temp_env::with_var(GOUP_HOME, Some(target.as_os_str()), || {
+            assert_eq!(import_bundle(&bundle)?, created);
+            assert_eq!(fs::read(target.cache_file(ARCHIVE))?, b"go1.21.9 archive");
+            assert_eq!(
+                fs::read_to_string(target.cache_file(utils::archive_sha256(ARCHIVE)))?,
+                sha256
+            );
+            let cached = op::list_cached_go_releases()?;
+            assert_eq!(cached.len(), 1);
+            assert_eq!(cached[0].version, "1.21.9");
+            assert!(leftovers(&target)?.is_empty());
+            anyhow::Ok(())
+        })
+    }
+
+    #[test]
+    fn test_import_bundle_mismatch() -> anyhow::Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        let target = Dir::new(tmp_dir.path().join("target"));
+        let sha256 = seed(&target, b"go1.21.9 archive")?;
+
+        // the archive is tampered with, the sha256 is not.
+        let bundle = tmp_dir.path().join("go-bundle.tar");
+        let mut tar = tar::Builder::new(File::create(&bundle)?);
+        append_data(&mut tar, ARCHIVE, b"tampered")?;
+        append_data(&mut tar, &utils::archive_sha256(ARCHIVE), sha256.as_bytes())?;
+        append_data(
+            &mut tar,
+            BUNDLE_RELEASE_INDEX,
+            &fs::read(target.release_index())?,
+        )?;
+        tar.into_inner()?.flush()?;
+
+        temp_env::with_var(GOUP_HOME, Some(target.as_os_str()), || {
+            assert!(import_bundle(&bundle).is_err());
+            // the cached archive is kept.
+            assert_eq!(fs::read(target.cache_file(ARCHIVE))?, b"go1.21.9 archive");
+            assert!(leftovers(&target)?.is_empty());
+            anyhow::Ok(())
+        })
+    }
+}
@@ -1,6 +1,5 @@
-use std::io::Read;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time;
 use std::{fs, fs::File};
 
@@ -8,20 +7,23 @@ use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use reqwest::blocking;
 use reqwest::blocking::Client;
 use reqwest::header::CONTENT_LENGTH;
-use sha2::{Digest, Sha256};
 
-use goup_misc::Dir;
+use goup_misc::cache::{SharedCache, tmp_path};
+use goup_misc::manifest::{Manifest, compute_file_sha256};
+use goup_misc::op::{self, GoRelease};
+use goup_misc::progress;
+use goup_misc::sync::{GoupToml, PinCheck};
+use goup_misc::{Dir, Version};
 
 use crate::archived::Unpack;
 use crate::utils;
 
 pub fn install_go_version(version: &str) -> anyhow::Result<()> {
+    // the canonical directory name: 1.21.0-rc2 -> go1.21rc2
+    let version = &Version::normalize(version);
     let goup_home = Dir::goup_home()?;
-    let version_dest_dir = goup_home.version(version);
 
-    let mp = MultiProgress::new();
-    let sp = mp.add(ProgressBar::new_spinner());
-    sp.enable_steady_tick(time::Duration::from_millis(100));
+    let sp = progress::spinner();
     sp.set_message(format!("Installing {version}"));
 
     // 是否已解压成功并且存在
@@ -29,7 +31,46 @@ pub fn install_go_version(version: &str) -> anyhow::Result<()> {
         sp.finish_with_message(format!("Already installed {version}"));
         return Ok(());
     }
+    // 外部链接的版本, 不能解压到链接目标中
+    if goup_home.is_dot_linked_file_exists(version) {
+        sp.finish_and_clear();
+        anyhow::bail!("{version} is a linked toolchain, unlink it with `goup remove` first.");
+    }
+
+    let archive_file = fetch_go_version_archive(&goup_home, version, &sp)?;
+    unpack_go_version(&goup_home, version, &archive_file, &sp)?;
+    sp.finish_with_message(format!("Installed {version}"));
 
+    Ok(())
+}
+
+/// download_go_version 下载并校验压缩包到缓存目录, 不解压, 返回压缩包路径
+pub fn download_go_version(version: &str) -> anyhow::Result<PathBuf> {
+    let version = &Version::normalize(version);
+    let goup_home = Dir::goup_home()?;
+    let sp = progress::spinner();
+    let archive_file = fetch_go_version_archive(&goup_home, version, &sp);
+    sp.finish_and_clear();
+    archive_file
+}
+
+/// verify_pinned_sha256 校验 goup.toml 固定的sha256, 缓存中没有压缩包时先下载,
+/// 如缺失的版本, 或已安装但压缩包已被清理的版本.
+pub fn verify_pinned_sha256(manifest: &GoupToml, version: &str) -> anyhow::Result<PinCheck> {
+    let archive = utils::go_version_archive(&Version::normalize(version));
+    if manifest.check_pinned_sha256(&archive)? == PinCheck::Unknown {
+        download_go_version(version)?;
+    }
+    manifest.check_pinned_sha256(&archive)
+}
+
+/// fetch_go_version_archive 从缓存目录, 共享缓存或网络获取压缩包并校验sha256
+fn fetch_go_version_archive(
+    goup_home: &Dir,
+    version: &str,
+    sp: &ProgressBar,
+) -> anyhow::Result<PathBuf> {
+    let mp = progress::multi_progress();
     // download directory
     let dl_dest_dir = goup_home.cache();
     // 压缩包文件名称
@@ -48,26 +89,82 @@ pub fn install_go_version(version: &str) -> anyhow::Result<()> {
     let archive_file = dl_dest_dir.join_path(archive_filename);
     let archive_sha256_file = dl_dest_dir.join_path(archive_sha256_filename);
 
-    if !archive_file.exists() || !archive_sha256_file.exists() {
+    let download = || -> anyhow::Result<()> {
         // 下载压缩包
         sp.set_message(format!("Downloading {archive_url}"));
-        download_archive(&mp, &archive_file, &archive_url)?;
+        log::debug!("downloading {archive_url}");
+        download_archive(mp, &archive_file, &archive_url)?;
 
         // 下载压缩包sha256
         sp.set_message(format!("Downloading {archive_sha256_url}"));
-        download_archive_sha256(&archive_sha256_file, &archive_sha256_url)?;
+        download_archive_sha256(&archive_sha256_file, &archive_sha256_url)
+    };
+
+    let shared_cache = SharedCache::from_env();
+    let mut shared = false;
+    if !archive_file.exists() || !archive_sha256_file.exists() {
+        // 先从共享缓存获取, 没有再下载
+        if let Some(shared_cache) = &shared_cache {
+            shared = fetch_shared_archive(
+                shared_cache,
+                &archive_file,
+                &archive_sha256_file,
+                &archive_sha256_url,
+            )?;
+        }
+        if !shared {
+            download()?;
+        }
     }
 
     // 校验压缩包sha256
     sp.set_message(format!("Verifying {}", archive_sha256_file.display()));
-    let ok = verify_archive_file_sha256(&archive_file, &archive_sha256_file)?;
+    let mut ok = verify_archive_file_sha256(&archive_file, &archive_sha256_file)?;
+    // 共享缓存中的压缩包损坏, 移除后重新下载
+    if !ok
+        && shared
+        && let Some(shared_cache) = &shared_cache
+    {
+        let sha256 = fs::read_to_string(&archive_sha256_file)?;
+        log::warn!(
+            "{} in the shared cache is corrupted, downloading it again",
+            archive_file.display()
+        );
+        if let Err(e) = shared_cache.evict(sha256.trim()) {
+            log::warn!("failed to evict it from the shared cache: {e}");
+        }
+        fs::remove_file(&archive_file)?;
+        download()?;
+        ok = verify_archive_file_sha256(&archive_file, &archive_sha256_file)?;
+    }
     if !ok {
-        // TODO: here should remove the bad archive_file.
+        fs::remove_file(&archive_file)?;
         anyhow::bail!("Hashsum NOT match {}", archive_sha256_file.display());
     }
+    // 校验通过后放入共享缓存, 失败不影响安装
+    if let Some(shared_cache) = &shared_cache {
+        let sha256 = fs::read_to_string(&archive_sha256_file)?;
+        if let Err(e) = shared_cache.store(&archive_file, sha256.trim()) {
+            log::warn!(
+                "failed to store {} in the shared cache: {e}",
+                archive_file.display()
+            );
+        }
+    }
+
+    Ok(archive_file.to_path_buf())
+}
 
+/// unpack_go_version 解压到 `{GOUP_HOME}/{name}`, 记录文件清单并设置解压成功
+fn unpack_go_version(
+    goup_home: &Dir,
+    name: &str,
+    archive_file: &Path,
+    sp: &ProgressBar,
+) -> anyhow::Result<()> {
     // 解压
     sp.set_message(format!("Unpacking {}", archive_file.display()));
+    let version_dest_dir = goup_home.version(name);
     if !version_dest_dir.exists() {
         log::debug!("Create version directory: {}", version_dest_dir.display());
         fs::create_dir_all(&version_dest_dir)?
@@ -75,22 +172,149 @@ pub fn install_go_version(version: &str) -> anyhow::Result<()> {
     archive_file
         .to_string_lossy()
         .parse::<Unpack>()?
-        .unpack(&version_dest_dir, &archive_file)?;
-    sp.finish_and_clear();
+        .unpack(&version_dest_dir, archive_file)?;
+
+    // 记录解压文件清单, 供 `goup verify` 校验
+    sp.set_message(format!("Recording manifest of {name}"));
+    Manifest::build(goup_home.version_go(name))?.save(goup_home.version_dot_manifest(name))?;
 
     // 设置解压成功
-    goup_home.create_dot_unpacked_success_file(version)?;
-    sp.finish_with_message(format!("Installed {version}"));
+    goup_home.create_dot_unpacked_success_file(name)?;
+    Ok(())
+}
+
+/// check_go_release 下载前校验版本已发布, 并且有当前平台的压缩包
+pub fn check_go_release(version: &str, releases: &[GoRelease]) -> anyhow::Result<()> {
+    let version = &Version::normalize(version);
+    op::check_go_release(version, &utils::go_version_archive(version), releases)
+}
+
+/// repair_go_version 使用缓存的压缩包重新安装, 不会下载
+pub fn repair_go_version(version: &str) -> anyhow::Result<()> {
+    let version = &Version::normalize(version);
+    let goup_home = Dir::goup_home()?;
+    let archive_filename = utils::go_version_archive(version);
+    let archive_file = goup_home.cache_file(&archive_filename);
+    let archive_sha256_file = goup_home.cache_file(utils::archive_sha256(&archive_filename));
+    if !archive_file.exists() || !archive_sha256_file.exists() {
+        anyhow::bail!(
+            "the archive of {version} is not cached, reinstall it with `goup remove {version}` and `goup install ={}`.",
+            version.trim_start_matches("go")
+        );
+    }
+    if !verify_archive_file_sha256(&archive_file, &archive_sha256_file)? {
+        anyhow::bail!("Hashsum NOT match {}", archive_sha256_file.display());
+    }
 
+    // 先解压到暂存目录, 成功后再替换, 失败时保留原目录
+    let staging = format!(".{version}.repair");
+    let staging_dir = goup_home.version(&staging);
+    if staging_dir.exists() {
+        fs::remove_dir_all(&staging_dir)?;
+    }
+    let sp = progress::spinner();
+    sp.set_message(format!("Repairing {version}"));
+    let unpacked = unpack_go_version(&goup_home, &staging, &archive_file, &sp);
+    sp.finish_and_clear();
+    if let Err(e) = unpacked {
+        let _ = fs::remove_dir_all(&staging_dir);
+        return Err(e);
+    }
+
+    let version_dest_dir = goup_home.version(version);
+    let old_dir = goup_home.version(format!(".{version}.old"));
+    if old_dir.exists() {
+        fs::remove_dir_all(&old_dir)?;
+    }
+    if version_dest_dir.exists() {
+        fs::rename(&version_dest_dir, &old_dir)?;
+    }
+    if let Err(e) = fs::rename(&staging_dir, &version_dest_dir) {
+        // 恢复原目录
+        if old_dir.exists() {
+            fs::rename(&old_dir, &version_dest_dir)?;
+        }
+        let _ = fs::remove_dir_all(&staging_dir);
+        return Err(e.into());
+    }
+    if old_dir.exists()
+        && let Err(e) = fs::remove_dir_all(&old_dir)
+    {
+        log::warn!("failed to remove {}: {e}", old_dir.display());
+    }
     Ok(())
 }
 
-/// download_archive 下载压缩包
-fn download_archive<P: AsRef<Path>>(
+/// fetch_shared_archive 从共享缓存获取压缩包, 缓存中没有时返回false.
+/// sha256 先从缓存的发布索引中查找, 没有再下载, 下载失败视为缓存中没有
+fn fetch_shared_archive(
+    shared_cache: &SharedCache,
+    archive_file: &Path,
+    archive_sha256_file: &Path,
+    archive_sha256_url: &str,
+) -> anyhow::Result<bool> {
+    let filename = archive_file
+        .file_name()
+        .map(|v| v.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let indexed = op::list_cached_go_releases()
+        .unwrap_or_default()
+        .into_iter()
+        .flat_map(|r| r.files)
+        .find(|f| f.filename == filename && !f.sha256.is_empty())
+        .map(|f| f.sha256);
+    let sha256 = match indexed {
+        Some(sha256) => sha256,
+        None => match download_sha256_text(archive_sha256_url) {
+            Ok(sha256) => sha256,
+            Err(e) => {
+                log::debug!("skip the shared cache, {e}");
+                return Ok(false);
+            }
+        },
+    };
+    match shared_cache.fetch(sha256.trim(), archive_file) {
+        Ok(true) => {}
+        Ok(false) => return Ok(false),
+        Err(e) => {
+            log::warn!("failed to fetch {filename} from the shared cache: {e}");
+            return Ok(false);
+        }
+    }
+    log::debug!("fetched {} from the shared cache", archive_file.display());
+    fs::write(archive_sha256_file, sha256)?;
+    Ok(true)
+}
+
+fn download_sha256_text(archive_sha256_url: &str) -> anyhow::Result<String> {
+    let response = blocking::get(archive_sha256_url)?;
+    if !response.status().is_success() {
+        anyhow::bail!(
+            "Downloading {archive_sha256_url} failure: {}",
+            response.status()
+        );
+    }
+    Ok(response.text()?)
+}
+
+/// download_archive 下载压缩包, 先写入临时文件再替换 dest.
+/// dest 可能是共享缓存的硬链接, 原地写入会改写其他用户的压缩包.
+pub(crate) fn download_archive<P: AsRef<Path>>(
     mp: &MultiProgress,
     dest: P,
     archive_url: &str,
 ) -> anyhow::Result<()> {
+    let dest = dest.as_ref();
+    let tmp = tmp_path(dest);
+    if let Err(e) = download_archive_to(mp, &tmp, archive_url) {
+        let _ = fs::remove_file(&tmp);
+        return Err(e);
+    }
+    fs::rename(&tmp, dest)?;
+    Ok(())
+}
+
+fn download_archive_to(mp: &MultiProgress, dest: &Path, archive_url: &str) -> anyhow::Result<()> {
     let client = Client::new();
 
     let resp = client
@@ -98,10 +322,13 @@ fn download_archive<P: AsRef<Path>>(
         .header("User-Agent", "GOUP Client")
         .timeout(time::Duration::from_secs(10))
         .send()?;
-    let headers = resp.headers();
-    let content_length = headers
+    if !resp.status().is_success() {
+        anyhow::bail!("Downloading {archive_url} failure: {}", resp.status());
+    }
+    let content_length = resp
+        .headers()
         .get(CONTENT_LENGTH)
-        .unwrap()
+        .ok_or_else(|| anyhow::anyhow!("Downloading {archive_url} failure: no Content-Length"))?
         .to_str()?
         .parse::<u64>()?;
 
@@ -158,7 +385,7 @@ fn download_archive<P: AsRef<Path>>(
 }
 
 /// download_archive_sha256 下载压缩包sha256
-fn download_archive_sha256<P: AsRef<Path>>(
+pub(crate) fn download_archive_sha256<P: AsRef<Path>>(
     dest: P,
     archive_sha256_url: &str,
 ) -> anyhow::Result<()> {
@@ -171,23 +398,8 @@ fn download_archive_sha256<P: AsRef<Path>>(
     Ok(())
 }
 
-/// compute_file_sha256 计算文件的sha256
-fn compute_file_sha256<P: AsRef<Path>>(path: P) -> anyhow::Result<String> {
-    let mut context = Sha256::new();
-    let mut file = File::open(path)?;
-    let mut buffer = [0; 4096]; // 定义一个缓冲区来处理字节流数据
-    loop {
-        let bytes_read = file.read(&mut buffer)?;
-        if bytes_read == 0 {
-            break;
-        }
-        context.update(&buffer[..bytes_read]);
-    }
-    Ok(format!("{:x}", context.finalize()))
-}
-
 /// verify_archive_file_sha256 校验文件压缩包的sha256
-fn verify_archive_file_sha256<P1, P2>(
+pub(crate) fn verify_archive_file_sha256<P1, P2>(
     archive_file: P1,
     archive_sha256_file: P2,
 ) -> anyhow::Result<bool>
@@ -201,3 +413,90 @@ where
 
     Ok(expect_sha256 == got_sha256)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use goup_misc::consts::{GOUP_CACHE_DIR, GOUP_GO_DOWNLOAD_BASE_URL, GOUP_HOME};
+
+    use super::*;
+    use crate::MirrorServer;
+
+    #[test]
+    fn test_verify_pinned_sha256() -> anyhow::Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        let archive = utils::go_version_archive("go1.21.9");
+        let upstream = Dir::new(tmp_dir.path().join("upstream"));
+        fs::create_dir_all(upstream.cache())?;
+        fs::write(upstream.cache_file(&archive), "go1.21.9 archive")?;
+        let sha256 = compute_file_sha256(upstream.cache_file(&archive))?;
+        fs::write(
+            upstream.cache_file(utils::archive_sha256(&archive)),
+            &sha256,
+        )?;
+        let server = temp_env::with_var(GOUP_HOME, Some(upstream.as_os_str()), || {
+            MirrorServer::bind("127.0.0.1:0", None)
+        })?;
+        let base_url = format!("http://{}/dl", server.local_addr());
+        thread::spawn(move || server.run());
+
+        // installed, but the archive was pruned from the cache.
+        let goup_home = Dir::new(tmp_dir.path().join("home"));
+        let manifest = |pinned: &str| -> anyhow::Result<GoupToml> {
+            format!("versions = [\"1.21.9\"]\n[sha256]\n\"{archive}\" = \"{pinned}\"\n").parse()
+        };
+        temp_env::with_vars(
+            [
+                (GOUP_HOME, Some(goup_home.as_os_str().to_owned())),
+                (GOUP_GO_DOWNLOAD_BASE_URL, Some(base_url.into())),
+                (GOUP_CACHE_DIR, None),
+            ],
+            || {
+                let matched = manifest(&sha256)?;
+                assert_eq!(matched.check_pinned_sha256(&archive)?, PinCheck::Unknown);
+                assert_eq!(verify_pinned_sha256(&matched, "1.21.9")?, PinCheck::Match);
+                assert!(goup_home.cache_file(&archive).is_file());
+
+                let pinned = "0".repeat(64);
+                assert_eq!(
+                    verify_pinned_sha256(&manifest(&pinned)?, "1.21.9")?,
+                    PinCheck::Mismatch {
+                        expected: pinned,
+                        actual: sha256.clone(),
+                    }
+                );
+                anyhow::Ok(())
+            },
+        )
+    }
+
+    #[test]
+    fn test_download_archive_over_shared() -> anyhow::Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        let archive = "go1.21.9.linux-amd64.tar.gz";
+        let upstream = Dir::new(tmp_dir.path().join("upstream"));
+        fs::create_dir_all(upstream.cache())?;
+        fs::write(upstream.cache_file(archive), "upstream archive")?;
+        let server = temp_env::with_var(GOUP_HOME, Some(upstream.as_os_str()), || {
+            MirrorServer::bind("127.0.0.1:0", None)
+        })?;
+        let url = format!("http://{}/dl/{archive}", server.local_addr());
+        thread::spawn(move || server.run());
+
+        let shared = SharedCache::new(tmp_dir.path().join("shared"));
+        let src = tmp_dir.path().join(archive);
+        fs::write(&src, "shared archive")?;
+        let sha256 = compute_file_sha256(&src)?;
+        shared.store(&src, &sha256)?;
+        let goup_home = Dir::new(tmp_dir.path().join("user"));
+        fs::create_dir_all(goup_home.cache())?;
+        let linked = goup_home.cache_file(archive);
+        assert!(shared.fetch(&sha256, &linked)?);
+
+        download_archive(&MultiProgress::new(), &linked, &url)?;
+        assert_eq!(fs::read_to_string(&linked)?, "upstream archive");
+        assert_eq!(fs::read_to_string(shared.blob(&sha256)?)?, "shared archive");
+        Ok(())
+    }
+}
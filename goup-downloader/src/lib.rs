@@ -1,5 +1,17 @@
 mod archived;
+mod bundle;
 mod downloader;
+mod mirror;
+mod proxy;
+mod serve;
 mod utils;
 
-pub use downloader::install_go_version;
+pub use bundle::{BundleReport, create_bundle, import_bundle};
+pub use downloader::{
+    check_go_release, download_go_version, install_go_version, repair_go_version,
+    verify_pinned_sha256,
+};
+pub use mirror::{MirrorReport, sync_mirror};
+pub use proxy::{SUMDB_URL, ToolchainProxy};
+pub use serve::MirrorServer;
+pub use utils::go_version_archive;
@@ -0,0 +1,269 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::anyhow;
+
+use goup_misc::manifest::compute_file_sha256;
+use goup_misc::op::{self, GoFile, GoRelease};
+use goup_misc::{GoVersion, GoVersionReq, progress};
+
+use crate::downloader::download_archive;
+use crate::utils;
+
+/// the directory of the archives and the release index in a mirror, `GOUP_GO_DOWNLOAD_BASE_URL`
+/// points at it.
+const MIRROR_DL_DIR: &str = "dl";
+/// the release index, a static web server serves it for `/dl/?mode=json&include=all`.
+const MIRROR_RELEASE_INDEX: &str = "index.html";
+/// the newest stable version, served for `/VERSION?m=text`.
+const MIRROR_VERSION: &str = "VERSION";
+
+/// what `sync_mirror` did.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MirrorReport {
+    /// the versions, such as `1.21.9`.
+    pub versions: Vec<String>,
+    pub downloaded: usize,
+    /// the archives already present and verified.
+    pub skipped: usize,
+    /// total size of the downloaded archives.
+    pub bytes: u64,
+}
+
+/// sync_mirror downloads the archives of the versions for the platforms into a static
+/// mirror directory, with sidecar sha256 files and a release index like upstream, which
+/// any static web server serves to goup. the platforms are `{os}/{arch}`, the current
+/// platform if empty. the archives already present and verified are skipped.
+pub fn sync_mirror(
+    dest: &Path,
+    versions: &[String],
+    platforms: &[String],
+    include_unstable: bool,
+    host: &str,
+) -> anyhow::Result<MirrorReport> {
+    let platforms = utils::parse_platforms(platforms)?;
+    let releases = op::list_upstream_go_releases(host)?;
+    let available: Vec<GoVersion> = releases
+        .iter()
+        .filter_map(|r| r.version.parse().ok())
+        .collect();
+
+    let mut selected: Vec<GoVersion> = Vec::new();
+    for ver in versions {
+        let req: GoVersionReq = ver.parse()?;
+        let matched = if include_unstable {
+            req.filter_with_unstable(&available)
+        } else {
+            req.filter(&available)
+        };
+        if matched.is_empty() {
+            anyhow::bail!("No Go version matches '{ver}'.");
+        }
+        for v in matched {
+            if !selected.contains(v) {
+                selected.push(v.clone());
+            }
+        }
+    }
+    selected.sort_by(|a, b| b.cmp(a));
+
+    let dl_dir = dest.join(MIRROR_DL_DIR);
+    fs::create_dir_all(&dl_dir)?;
+    let mp = progress::multi_progress();
+    let sp = progress::spinner();
+    let mut report = MirrorReport::default();
+    let mut index: Vec<GoRelease> = Vec::new();
+    for ver in &selected {
+        let version = ver.to_string();
+        let Some(release) = releases.iter().find(|r| r.version == version) else {
+            continue;
+        };
+        let files: Vec<GoFile> = release
+            .files
+            .iter()
+            .filter(|f| {
+                f.kind == "archive"
+                    && platforms
+                        .iter()
+                        .any(|(os, arch)| *os == f.os && *arch == f.arch)
+            })
+            .cloned()
+            .collect();
+        for (os, arch) in &platforms {
+            if !files.iter().any(|f| f.os == *os && f.arch == *arch) {
+                log::warn!("Go version {version} is not built for {os}/{arch}, skipped.");
+            }
+        }
+        for file in &files {
+            let archive = dl_dir.join(&file.filename);
+            let sha256_file = dl_dir.join(utils::archive_sha256(&file.filename));
+            if is_mirrored(&archive, &sha256_file, file)? {
+                log::debug!("{} is already mirrored", file.filename);
+                report.skipped += 1;
+                continue;
+            }
+            let (archive_url, _) = utils::archive_url(&file.filename);
+            sp.set_message(format!("Downloading {archive_url}"));
+            let tmp = dl_dir.join(format!(".{}.download", file.filename));
+            download_archive(mp, &tmp, &archive_url)?;
+            sp.set_message(format!("Verifying {}", file.filename));
+            if compute_file_sha256(&tmp)? != file.sha256 {
+                fs::remove_file(&tmp)?;
+                anyhow::bail!("Hashsum NOT match {}", file.filename);
+            }
+            fs::rename(&tmp, &archive)?;
+            // the sidecar is written after the archive is verified, see `is_mirrored`.
+            fs::write(&sha256_file, &file.sha256)?;
+            report.downloaded += 1;
+            report.bytes += fs::metadata(&archive)?.len();
+        }
+        if !files.is_empty() {
+            report.versions.push(version);
+            index.push(GoRelease {
+                version: format!("go{}", release.version),
+                stable: release.stable,
+                files,
+            });
+        }
+    }
+    sp.finish_and_clear();
+
+    // the releases mirrored by the previous syncs are kept.
+    let index_file = dl_dir.join(MIRROR_RELEASE_INDEX);
+    let mut merged: Vec<GoRelease> = if index_file.exists() {
+        serde_json::from_str(&fs::read_to_string(&index_file)?)
+            .map_err(|e| anyhow!("{} is not a release index: {e}", index_file.display()))?
+    } else {
+        Vec::new()
+    };
+    op::merge_releases(&mut merged, index);
+    write_atomically(&index_file, &serde_json::to_vec(&merged)?)?;
+    if let Some(latest) = merged.iter().find(|r| r.stable) {
+        write_atomically(&dest.join(MIRROR_VERSION), format!("{}\n", latest.version))?;
+    }
+    Ok(report)
+}
+
+/// the archive is mirrored and matches the release index. the size and the sidecar are
+/// checked first, so a stale or missing archive is not hashed.
+fn is_mirrored(archive: &Path, sha256_file: &Path, file: &GoFile) -> anyhow::Result<bool> {
+    let size_matched =
+        fs::metadata(archive).is_ok_and(|m| file.size <= 0 || m.len() as isize == file.size);
+    let sidecar_matched =
+        fs::read_to_string(sha256_file).is_ok_and(|s| s.trim().eq_ignore_ascii_case(&file.sha256));
+    Ok(size_matched
+        && sidecar_matched
+        && compute_file_sha256(archive)?.eq_ignore_ascii_case(&file.sha256))
+}
+
+fn write_atomically<C: AsRef<[u8]>>(path: &Path, contents: C) -> anyhow::Result<()> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use goup_misc::Dir;
+    use goup_misc::consts::{GOUP_GO_DOWNLOAD_BASE_URL, GOUP_HOME};
+
+    use super::*;
+    use crate::MirrorServer;
+
+    /// an upstream of go1.21.9 and go1.22.3 for linux/amd64, served by a `MirrorServer`.
+    fn upstream(goup_home: &Dir) -> anyhow::Result<String> {
+        fs::create_dir_all(goup_home.cache())?;
+        let mut index = Vec::new();
+        for version in ["1.22.3", "1.21.9"] {
+            let filename = format!("go{version}.linux-amd64.tar.gz");
+            let archive = goup_home.cache_file(&filename);
+            fs::write(&archive, format!("go{version} archive"))?;
+            let sha256 = compute_file_sha256(&archive)?;
+            fs::write(
+                goup_home.cache_file(utils::archive_sha256(&filename)),
+                &sha256,
+            )?;
+            index.push(GoRelease {
+                version: format!("go{version}"),
+                stable: true,
+                files: vec![GoFile {
+                    arch: "amd64".to_owned(),
+                    filename,
+                    kind: "archive".to_owned(),
+                    os: "linux".to_owned(),
+                    sha256,
+                    size: fs::metadata(&archive)?.len() as isize,
+                    version: format!("go{version}"),
+                }],
+            });
+        }
+        fs::write(goup_home.release_index(), serde_json::to_vec(&index)?)?;
+        let server = temp_env::with_var(GOUP_HOME, Some(goup_home.as_os_str()), || {
+            MirrorServer::bind("127.0.0.1:0", None)
+        })?;
+        let addr = format!("http://{}", server.local_addr());
+        thread::spawn(move || server.run());
+        Ok(addr)
+    }
+
+    #[test]
+    fn test_sync_mirror() -> anyhow::Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        let host = upstream(&Dir::new(tmp_dir.path().join("upstream")))?;
+        let dest = tmp_dir.path().join("mirror");
+        let archive = dest.join(MIRROR_DL_DIR).join("go1.21.9.linux-amd64.tar.gz");
+        let sync = |version: &str| {
+            sync_mirror(
+                &dest,
+                &[version.to_owned()],
+                &["linux/amd64".to_owned()],
+                false,
+                &host,
+            )
+        };
+        let index = || -> anyhow::Result<Vec<String>> {
+            let index: Vec<GoRelease> = serde_json::from_str(&fs::read_to_string(
+                dest.join(MIRROR_DL_DIR).join(MIRROR_RELEASE_INDEX),
+            )?)?;
+            Ok(index.into_iter().map(|r| r.version).collect())
+        };
+
+        temp_env::with_vars(
+            [
+                (
+                    GOUP_HOME,
+                    Some(tmp_dir.path().join("home").into_os_string()),
+                ),
+                (GOUP_GO_DOWNLOAD_BASE_URL, Some(format!("{host}/dl").into())),
+            ],
+            || {
+                let report = sync("1.21")?;
+                assert_eq!(report.versions, ["1.21.9"]);
+                assert_eq!((report.downloaded, report.skipped), (1, 0));
+                assert_eq!(fs::read_to_string(&archive)?, "go1.21.9 archive");
+                assert_eq!(index()?, ["go1.21.9"]);
+                assert_eq!(fs::read_to_string(dest.join(MIRROR_VERSION))?, "go1.21.9\n");
+
+                // incremental, the verified archive is skipped.
+                let report = sync("1.21")?;
+                assert_eq!((report.downloaded, report.skipped), (0, 1));
+
+                // corrupted with the same size, the sidecar is not trusted.
+                fs::write(&archive, "go1.21.9 ARCHIVE")?;
+                let report = sync("1.21")?;
+                assert_eq!((report.downloaded, report.skipped), (1, 0));
+                assert_eq!(fs::read_to_string(&archive)?, "go1.21.9 archive");
+
+                // merged with the releases mirrored before.
+                let report = sync("1.22")?;
+                assert_eq!(report.versions, ["1.22.3"]);
+                assert_eq!(index()?, ["go1.22.3", "go1.21.9"]);
+                assert_eq!(fs::read_to_string(dest.join(MIRROR_VERSION))?, "go1.22.3\n");
+                anyhow::Ok(())
+            },
+        )
+    }
+}
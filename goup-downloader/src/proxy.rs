@@ -0,0 +1,788 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::anyhow;
+use flate2::read::GzDecoder;
+use reqwest::StatusCode;
+use tiny_http::{Request, Response, ResponseBox, Server};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use goup_misc::cache::tmp_path;
+use goup_misc::op;
+use goup_misc::{Dir, GoVersion};
+
+use crate::serve::{self, Route, content_type, fetch_archive, file_response, text_response};
+use crate::utils;
+
+/// the module of the Go toolchains which the go command downloads to switch toolchains.
+const TOOLCHAIN_MODULE: &str = "golang.org/toolchain";
+/// the go.mod of the toolchain module, served as the `.mod`, the module zips have none.
+const TOOLCHAIN_GO_MOD: &str = "module golang.org/toolchain\n";
+
+/// the checksum database proxied at `/sumdb/sum.golang.org`.
+const SUMDB_NAME: &str = "sum.golang.org";
+/// the official server of the checksum database.
+pub const SUMDB_URL: &str = "https://sum.golang.org";
+
+/// a GOPROXY serving the `golang.org/toolchain` module, so the toolchain switching of the
+/// go command(`GOTOOLCHAIN`) is satisfied by the archives in the cache or the installed
+/// versions. the module zips are synthesized from them with the files of the official
+/// ones, so they pass the checksum database, which is proxied too.
+pub struct ToolchainProxy {
+    server: Server,
+    proxy: Arc<Proxy>,
+}
+
+struct Proxy {
+    goup_home: Dir,
+    /// download the archives from `GOUP_GO_DOWNLOAD_BASE_URL` on a cache miss.
+    fetch: bool,
+    /// synthesizing is serialized, so a module zip is synthesized once.
+    synthesizing: Mutex<()>,
+    /// where the checksum database requests are forwarded to.
+    sumdb_url: String,
+}
+
+/// a toolchain of a platform, the module version is `v0.0.1-go1.21.9.linux-amd64`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Toolchain {
+    version: GoVersion,
+    os: String,
+    arch: String,
+}
+
+/// where a module zip is synthesized from.
+enum Source {
+    /// the archive in the cache.
+    Archive(PathBuf),
+    /// the GOROOT of an installed version.
+    Installed(PathBuf),
+}
+
+impl ToolchainProxy {
+    /// bind the proxy to the address, such as `127.0.0.1:8081`. the checksum database is
+    /// forwarded to `sumdb_url`, such as [`SUMDB_URL`].
+    pub fn bind(listen: &str, fetch: bool, sumdb_url: &str) -> anyhow::Result<Self> {
+        let server =
+            Server::http(listen).map_err(|e| anyhow!("Listening on {listen} failed: {e}"))?;
+        Ok(Self {
+            server,
+            proxy: Arc::new(Proxy {
+                goup_home: Dir::goup_home()?,
+                fetch,
+                synthesizing: Mutex::new(()),
+                sumdb_url: sumdb_url.trim_end_matches('/').to_owned(),
+            }),
+        })
+    }
+
+    /// the address listened on, such as `127.0.0.1:8081`.
+    pub fn local_addr(&self) -> String {
+        self.server.server_addr().to_string()
+    }
+
+    /// serve the requests until the process is stopped.
+    pub fn run(self) -> anyhow::Result<()> {
+        serve::serve(&self.server, self.proxy);
+        Ok(())
+    }
+}
+
+impl Route for Proxy {
+    /// the GOPROXY protocol, see `go help goproxy`. the other modules are not found, so the
+    /// go command falls back to the next proxy in `GOPROXY`.
+    fn route(&self, request: &Request) -> anyhow::Result<ResponseBox> {
+        let path = request.url().split('?').next().unwrap_or_default();
+        if let Some(rest) = path.strip_prefix("/sumdb/") {
+            return self.sumdb(rest);
+        }
+        let Some(rest) = path
+            .strip_prefix('/')
+            .and_then(|p| p.strip_prefix(TOOLCHAIN_MODULE))
+            .and_then(|p| p.strip_prefix("/@v/"))
+        else {
+            return Ok(text_response(404, "not found"));
+        };
+        if rest == "list" {
+            let list: String = self
+                .toolchains()?
+                .iter()
+                .map(|t| format!("{}\n", t.module_version()))
+                .collect();
+            return Ok(text_response(200, &list));
+        }
+        let Some((toolchain, ext)) = rest
+            .rsplit_once('.')
+            .and_then(|(v, ext)| Some((Toolchain::from_module_version(v)?, ext)))
+        else {
+            return Ok(text_response(404, "not found"));
+        };
+        let Some(source) = self.source(&toolchain) else {
+            return Ok(text_response(404, "not found"));
+        };
+        match ext {
+            "info" => {
+                let time = fs::metadata(source.path())?.modified()?;
+                let info = serde_json::json!({
+                    "Version": toolchain.module_version(),
+                    "Time": rfc3339(time),
+                });
+                Ok(Response::from_data(serde_json::to_vec(&info)?)
+                    .with_header(content_type("application/json"))
+                    .boxed())
+            }
+            "mod" => Ok(text_response(200, TOOLCHAIN_GO_MOD)),
+            "zip" => {
+                let module_zip = self.module_zip(&toolchain, &source)?;
+                Ok(file_response(&module_zip, request.headers())?
+                    .with_header(content_type("application/zip")))
+            }
+            _ => Ok(text_response(404, "not found")),
+        }
+    }
+}
+
+impl Proxy {
+    /// the toolchains of the cached archives and the installed versions. the archives in the
+    /// cached release index are listed too if fetching.
+    fn toolchains(&self) -> anyhow::Result<Vec<Toolchain>> {
+        let mut toolchains = Vec::new();
+        let cache = self.goup_home.cache();
+        if cache.is_dir() {
+            for entry in cache.read_dir()? {
+                let name = entry?.file_name().to_string_lossy().to_string();
+                if cache.join(utils::archive_sha256(&name)).exists()
+                    && let Some(t) = Toolchain::from_archive(&name)
+                {
+                    toolchains.push(t);
+                }
+            }
+        }
+        if self.goup_home.is_dir() {
+            let (os, arch) = utils::platform();
+            for entry in self.goup_home.read_dir()? {
+                let name = entry?.file_name().to_string_lossy().to_string();
+                if let Some(version) = name.strip_prefix("go").and_then(|v| v.parse().ok())
+                    && self.is_installed(&name)
+                {
+                    toolchains.push(Toolchain {
+                        version,
+                        os: os.to_owned(),
+                        arch: arch.to_owned(),
+                    });
+                }
+            }
+        }
+        if self.fetch {
+            toolchains.extend(
+                op::list_cached_go_releases()?
+                    .iter()
+                    .flat_map(|r| &r.files)
+                    .filter(|f| f.kind == "archive")
+                    .filter_map(|f| Toolchain::from_archive(&f.filename)),
+            );
+        }
+        toolchains.sort();
+        toolchains.dedup();
+        Ok(toolchains)
+    }
+
+    /// a version unpacked by goup, not linked, whose files are the archive's.
+    fn is_installed(&self, dir_name: &str) -> bool {
+        self.goup_home.is_dot_unpacked_success_file_exists(dir_name)
+            && !self.goup_home.is_dot_linked_file_exists(dir_name)
+    }
+
+    /// the cached archive, or the installed version of this platform. the archive is
+    /// downloaded if fetching, it is not found if downloading fails.
+    fn source(&self, toolchain: &Toolchain) -> Option<Source> {
+        let archive = toolchain.archive();
+        let cached = |archive: &str| {
+            let file = self.goup_home.cache_file(archive);
+            let sha256_file = self.goup_home.cache_file(utils::archive_sha256(archive));
+            (file.exists() && sha256_file.exists()).then(|| Source::Archive(file.to_path_buf()))
+        };
+        if let Some(source) = cached(&archive) {
+            return Some(source);
+        }
+        let dir_name = toolchain.version.dir_name();
+        if (toolchain.os.as_str(), toolchain.arch.as_str()) == utils::platform()
+            && self.is_installed(&dir_name)
+        {
+            return Some(Source::Installed(
+                self.goup_home.version_go(&dir_name).to_path_buf(),
+            ));
+        }
+        if !self.fetch {
+            return None;
+        }
+        let _guard = self.synthesizing.lock().ok()?;
+        if let Err(e) = fetch_archive(&self.goup_home, &archive) {
+            log::warn!("Fetching {archive} failed: {e}");
+            return None;
+        }
+        cached(&archive)
+    }
+
+    /// proxy the checksum database, see "Proxying a Checksum Database" of `go help goproxy`.
+    /// the responses are kept in `cache/sumdb`, and served when the database is unreachable,
+    /// so the toolchains looked up before are still verified offline.
+    fn sumdb(&self, path: &str) -> anyhow::Result<ResponseBox> {
+        let Some(rest) = path
+            .strip_prefix(SUMDB_NAME)
+            .and_then(|p| p.strip_prefix('/'))
+        else {
+            return Ok(text_response(404, "not found"));
+        };
+        if rest == "supported" {
+            return Ok(text_response(200, ""));
+        }
+        if !is_sumdb_path(rest) {
+            return Ok(text_response(404, "not found"));
+        }
+        let cache_file = self.goup_home.sumdb_cache().join(SUMDB_NAME).join(rest);
+        let url = format!("{}/{rest}", self.sumdb_url);
+        let body = match fetch_sumdb(&url) {
+            Ok(Some(body)) => {
+                if let Some(dir) = cache_file.parent() {
+                    fs::create_dir_all(dir)?;
+                }
+                let tmp = tmp_path(&cache_file);
+                fs::write(&tmp, &body)?;
+                fs::rename(&tmp, &cache_file)?;
+                body
+            }
+            Ok(None) => return Ok(text_response(404, "not found")),
+            Err(e) if cache_file.is_file() => {
+                log::warn!("Fetching {url} failed, serving the cached one: {e}");
+                fs::read(&cache_file)?
+            }
+            Err(e) => return Ok(text_response(502, &format!("fetching {url} failed: {e}"))),
+        };
+        Ok(Response::from_data(body)
+            .with_header(content_type("application/octet-stream"))
+            .boxed())
+    }
+
+    /// the module zip in `cache/toolchain`, synthesized from the source if missing.
+    fn module_zip(&self, toolchain: &Toolchain, source: &Source) -> anyhow::Result<PathBuf> {
+        let module_version = toolchain.module_version();
+        let dir = self.goup_home.toolchain_modules();
+        let module_zip = dir.join(format!("{module_version}.zip"));
+        let _guard = self
+            .synthesizing
+            .lock()
+            .map_err(|_| anyhow!("synthesizing panicked"))?;
+        if module_zip.exists() {
+            return Ok(module_zip);
+        }
+        fs::create_dir_all(&dir)?;
+        log::info!("Synthesizing {TOOLCHAIN_MODULE}@{module_version}");
+        let tmp = dir.join(format!(".{module_version}.zip.tmp"));
+        let prefix = format!("{TOOLCHAIN_MODULE}@{module_version}/");
+        if let Err(e) = synthesize_module_zip(source, &prefix, &tmp) {
+            let _ = fs::remove_file(&tmp);
+            return Err(e);
+        }
+        fs::rename(&tmp, &module_zip)?;
+        Ok(module_zip)
+    }
+}
+
+impl Source {
+    fn path(&self) -> &Path {
+        match self {
+            Source::Archive(p) | Source::Installed(p) => p,
+        }
+    }
+}
+
+impl Toolchain {
+    /// parse the module version, such as `v0.0.1-go1.21.9.linux-amd64`.
+    fn from_module_version(module_version: &str) -> Option<Self> {
+        Self::parse(module_version.strip_prefix("v0.0.1-")?)
+    }
+
+    /// parse the archive file name, such as `go1.21.9.linux-amd64.tar.gz`.
+    fn from_archive(archive: &str) -> Option<Self> {
+        Self::parse(
+            archive
+                .strip_suffix(".tar.gz")
+                .or_else(|| archive.strip_suffix(".zip"))?,
+        )
+    }
+
+    /// parse `go1.21.9.linux-amd64`, the version must be canonical.
+    fn parse(s: &str) -> Option<Self> {
+        let (ver, platform) = s.strip_prefix("go")?.rsplit_once('.')?;
+        let (os, arch) = platform.split_once('-')?;
+        let version: GoVersion = ver.parse().ok()?;
+        let valid = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric());
+        (version.to_string() == ver && valid(os) && valid(arch)).then(|| Self {
+            version,
+            os: os.to_owned(),
+            arch: arch.to_owned(),
+        })
+    }
+
+    fn module_version(&self) -> String {
+        format!("v0.0.1-go{}.{}-{}", self.version, self.os, self.arch)
+    }
+
+    fn archive(&self) -> String {
+        let ext = if self.os == "windows" {
+            "zip"
+        } else {
+            "tar.gz"
+        };
+        format!("go{}.{}-{}.{ext}", self.version, self.os, self.arch)
+    }
+}
+
+/// a `lookup/{module}@{version}`, `tile/...` or `latest` path of the checksum database,
+/// never a path out of the cache.
+fn is_sumdb_path(path: &str) -> bool {
+    (path == "latest" || path.starts_with("lookup/") || path.starts_with("tile/"))
+        && !path.contains('\\')
+        && path
+            .split('/')
+            .all(|c| !c.is_empty() && !c.starts_with('.') && !c.ends_with(".tmp"))
+}
+
+/// the body of the checksum database response, `None` if it is not found.
+fn fetch_sumdb(url: &str) -> anyhow::Result<Option<Vec<u8>>> {
+    let response = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()?
+        .get(url)
+        .send()?;
+    match response.status() {
+        StatusCode::NOT_FOUND | StatusCode::GONE => Ok(None),
+        _ => Ok(Some(response.error_for_status()?.bytes()?.to_vec())),
+    }
+}
+
+/// the name in the module zip of a file under GOROOT, `None` if it is left out. the official
+/// module zips are built by `cmd/distpack` from the files of the archive: the `api`, `doc`,
+/// `misc` and `test` directories are left out, and the go.mod files are renamed to `_go.mod`
+/// as a module zip can't contain nested modules. the zip has no go.mod at the root, its
+/// `.mod` is served on its own, so the files hash to the `h1:` of the checksum database.
+fn module_file_name(name: &str) -> Option<String> {
+    let top = name.split('/').next().unwrap_or_default();
+    if ["api", "doc", "misc", "test"].contains(&top) {
+        return None;
+    }
+    match name.strip_suffix("go.mod") {
+        Some(dir) if dir.is_empty() || dir.ends_with('/') => Some(format!("{dir}_go.mod")),
+        _ => Some(name.to_owned()),
+    }
+}
+
+/// synthesize the module zip, the files of the `go` directory are under the prefix, see
+/// [`module_file_name`].
+fn synthesize_module_zip(source: &Source, prefix: &str, dest: &Path) -> anyhow::Result<()> {
+    let mut zip = ZipWriter::new(File::create(dest)?);
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .large_file(true);
+    match source {
+        Source::Archive(archive) if archive.to_string_lossy().ends_with(".zip") => {
+            let mut archive = ZipArchive::new(File::open(archive)?)?;
+            for i in 0..archive.len() {
+                let mut file = archive.by_index(i)?;
+                let Some(name) = file.name().strip_prefix("go/").and_then(module_file_name) else {
+                    continue;
+                };
+                if file.is_file() {
+                    zip.start_file(format!("{prefix}{name}"), options)?;
+                    io::copy(&mut file, &mut zip)?;
+                }
+            }
+        }
+        Source::Archive(archive) => {
+            let mut archive = tar::Archive::new(GzDecoder::new(File::open(archive)?));
+            for entry in archive.entries()? {
+                let mut entry = entry?;
+                let path = entry.path()?;
+                let Some(name) = path
+                    .to_str()
+                    .and_then(|p| p.strip_prefix("go/"))
+                    .and_then(module_file_name)
+                else {
+                    continue;
+                };
+                if entry.header().entry_type().is_file() {
+                    zip.start_file(format!("{prefix}{name}"), options)?;
+                    io::copy(&mut entry, &mut zip)?;
+                }
+            }
+        }
+        Source::Installed(goroot) => {
+            for file in walk_files(goroot)? {
+                let name = file
+                    .strip_prefix(goroot)?
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                let Some(name) = module_file_name(&name) else {
+                    continue;
+                };
+                zip.start_file(format!("{prefix}{name}"), options)?;
+                io::copy(&mut File::open(&file)?, &mut zip)?;
+            }
+        }
+    }
+    zip.finish()?.flush()?;
+    Ok(())
+}
+
+/// the regular files under the directory, sorted.
+fn walk_files(dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in dir.read_dir()? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                dirs.push(entry.path());
+            } else if file_type.is_file() {
+                files.push(entry.path());
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// format the time in RFC 3339 of UTC, such as `2023-08-08T18:32:48Z`.
+fn rfc3339(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, secs) = ((secs / 86400) as i64, secs % 86400);
+    // the civil date of the days since 1970-01-01, by Howard Hinnant's algorithm.
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use std::thread;
+
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use sha2::{Digest, Sha256};
+
+    use super::*;
+
+    /// the files of an archive, the last one is out of the `go` directory.
+    const FILES: [(&str, &str); 6] = [
+        ("go/VERSION", "go1.21.9"),
+        ("go/bin/go", "#!/bin/sh"),
+        ("go/src/go.mod", "module std"),
+        ("go/src/cmd/go.mod", "module cmd"),
+        ("go/test/run.go", "package main"),
+        ("other/README", "skipped"),
+    ];
+
+    fn module_zip_entries(source: &Source, dir: &Path) -> anyhow::Result<Vec<(String, String)>> {
+        let prefix = "golang.org/toolchain@v0.0.1-go1.21.9.linux-amd64/";
+        let dest = dir.join("module.zip");
+        synthesize_module_zip(source, prefix, &dest)?;
+        let mut zip = ZipArchive::new(File::open(dest)?)?;
+        let mut entries = Vec::new();
+        for i in 0..zip.len() {
+            let mut file = zip.by_index(i)?;
+            let mut content = String::new();
+            file.read_to_string(&mut content)?;
+            let name = file.name().strip_prefix(prefix).unwrap_or(file.name());
+            entries.push((name.to_owned(), content));
+        }
+        entries.sort();
+        Ok(entries)
+    }
+
+    fn expected_entries() -> Vec<(String, String)> {
+        let entries = [
+            ("VERSION", "go1.21.9"),
+            ("bin/go", "#!/bin/sh"),
+            ("src/_go.mod", "module std"),
+            ("src/cmd/_go.mod", "module cmd"),
+        ];
+        entries
+            .iter()
+            .map(|(name, content)| (name.to_string(), content.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_synthesize_module_zip() -> anyhow::Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+
+        let tar_gz = tmp_dir.path().join("go1.21.9.linux-amd64.tar.gz");
+        let mut tar = tar::Builder::new(GzEncoder::new(
+            File::create(&tar_gz)?,
+            Compression::default(),
+        ));
+        for (name, content) in FILES {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append_data(&mut header, name, content.as_bytes())?;
+        }
+        tar.into_inner()?.finish()?;
+        let entries = module_zip_entries(&Source::Archive(tar_gz), tmp_dir.path())?;
+        assert_eq!(entries, expected_entries());
+
+        let zip_file = tmp_dir.path().join("go1.21.9.windows-amd64.zip");
+        let mut zip = ZipWriter::new(File::create(&zip_file)?);
+        for (name, content) in FILES {
+            zip.start_file(name, SimpleFileOptions::default())?;
+            zip.write_all(content.as_bytes())?;
+        }
+        zip.finish()?;
+        let entries = module_zip_entries(&Source::Archive(zip_file), tmp_dir.path())?;
+        assert_eq!(entries, expected_entries());
+
+        let goroot = tmp_dir.path().join("go1.21.9").join("go");
+        for (name, content) in FILES {
+            if let Some(name) = name.strip_prefix("go/") {
+                let file = goroot.join(name);
+                fs::create_dir_all(file.parent().unwrap())?;
+                fs::write(file, content)?;
+            }
+        }
+        let entries = module_zip_entries(&Source::Installed(goroot), tmp_dir.path())?;
+        assert_eq!(entries, expected_entries());
+        Ok(())
+    }
+
+    /// the `h1:` checksum of the files, the `Hash1` of `golang.org/x/mod/sumdb/dirhash`.
+    fn hash1<'a>(files: impl IntoIterator<Item = (&'a str, &'a [u8])>) -> String {
+        let mut lines: Vec<String> = files
+            .into_iter()
+            .map(|(name, content)| format!("{:x}  {name}\n", Sha256::digest(content)))
+            .collect();
+        lines.sort_by(|a, b| a[66..].cmp(&b[66..]));
+        format!("h1:{}", STANDARD.encode(Sha256::digest(lines.concat())))
+    }
+
+    /// the `h1:` checksum of a module zip, whose file names keep the `module@version/` prefix.
+    fn zip_hash1(path: &Path) -> anyhow::Result<(Vec<String>, String)> {
+        let mut zip = ZipArchive::new(File::open(path)?)?;
+        let mut files = Vec::new();
+        for i in 0..zip.len() {
+            let mut file = zip.by_index(i)?;
+            let mut content = Vec::new();
+            file.read_to_end(&mut content)?;
+            files.push((file.name().to_owned(), content));
+        }
+        let hash = hash1(files.iter().map(|(n, c)| (n.as_str(), c.as_slice())));
+        let mut names: Vec<String> = files.into_iter().map(|(n, _)| n).collect();
+        names.sort();
+        Ok((names, hash))
+    }
+
+    #[test]
+    fn test_hash1() {
+        // `golang.org/x/text v0.3.0/go.mod` of the checksum database.
+        assert_eq!(
+            hash1([("go.mod", b"module golang.org/x/text\n".as_slice())]),
+            "h1:NqM8EUOU14njkJ3fqMW+pc6Ldnwhi/IjpwHt7yyuwOQ="
+        );
+    }
+
+    /// synthesize `golang.org/toolchain@v0.0.1-go1.21.0.linux-amd64` from the upstream archive,
+    /// its files must be the published zip's and its `h1:` the checksum database's. run it
+    /// with `cargo test -- --ignored`, it downloads about 130MB.
+    #[test]
+    #[ignore = "downloads the go1.21.0 archive and module zip"]
+    fn test_module_zip_golden() -> anyhow::Result<()> {
+        let module_version = "v0.0.1-go1.21.0.linux-amd64";
+        let tmp_dir = tempfile::tempdir()?;
+        let client = reqwest::blocking::Client::builder().timeout(None).build()?;
+        let download = |url: &str, dest: &Path| -> anyhow::Result<()> {
+            let body = client.get(url).send()?.error_for_status()?.bytes()?;
+            fs::write(dest, body)?;
+            Ok(())
+        };
+
+        let archive = tmp_dir.path().join("go1.21.0.linux-amd64.tar.gz");
+        download(
+            "https://dl.google.com/go/go1.21.0.linux-amd64.tar.gz",
+            &archive,
+        )?;
+        let synthesized = tmp_dir.path().join("synthesized.zip");
+        let prefix = format!("{TOOLCHAIN_MODULE}@{module_version}/");
+        synthesize_module_zip(&Source::Archive(archive), &prefix, &synthesized)?;
+
+        let published = tmp_dir.path().join("published.zip");
+        download(
+            &format!("https://proxy.golang.org/{TOOLCHAIN_MODULE}/@v/{module_version}.zip"),
+            &published,
+        )?;
+        let (names, hash) = zip_hash1(&synthesized)?;
+        let (published_names, published_hash) = zip_hash1(&published)?;
+        assert_eq!(names, published_names);
+        assert_eq!(hash, published_hash);
+
+        let lookup = client
+            .get(format!(
+                "{SUMDB_URL}/lookup/{TOOLCHAIN_MODULE}@{module_version}"
+            ))
+            .send()?
+            .error_for_status()?
+            .text()?;
+        let line = format!("{TOOLCHAIN_MODULE} {module_version} {hash}");
+        assert!(lookup.lines().any(|l| l == line), "{line} not in {lookup}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_module_file_name() {
+        assert_eq!(module_file_name("bin/go").as_deref(), Some("bin/go"));
+        assert_eq!(module_file_name("go.mod").as_deref(), Some("_go.mod"));
+        assert_eq!(
+            module_file_name("src/cmd/go.mod").as_deref(),
+            Some("src/cmd/_go.mod")
+        );
+        assert_eq!(
+            module_file_name("src/foo_go.mod").as_deref(),
+            Some("src/foo_go.mod")
+        );
+        assert_eq!(module_file_name("api/go1.txt"), None);
+        assert_eq!(module_file_name("doc/go_spec.html"), None);
+        assert_eq!(module_file_name("misc/wasm/wasm_exec.js"), None);
+        assert_eq!(module_file_name("test/run.go"), None);
+        assert_eq!(
+            module_file_name("src/cmd/api/main.go").as_deref(),
+            Some("src/cmd/api/main.go")
+        );
+    }
+
+    #[test]
+    fn test_sumdb() -> anyhow::Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        let proxy = Proxy {
+            goup_home: Dir::new(tmp_dir.path()),
+            fetch: false,
+            synthesizing: Mutex::new(()),
+            // nothing listens on it, the database is unreachable.
+            sumdb_url: "http://127.0.0.1:1".to_owned(),
+        };
+        let status = |path: &str| proxy.sumdb(path).map(|r| r.status_code().0);
+
+        assert_eq!(status("sum.golang.org/supported")?, 200);
+        assert_eq!(status("sum.golang.google.cn/supported")?, 404);
+        assert_eq!(status("sum.golang.org/lookup/../../releases.json")?, 404);
+        assert_eq!(status("sum.golang.org/tile/8/0/.x001")?, 404);
+
+        let lookup = "lookup/golang.org/toolchain@v0.0.1-go1.21.9.linux-amd64";
+        assert_eq!(status(&format!("sum.golang.org/{lookup}"))?, 502);
+        let cache_file = proxy.goup_home.sumdb_cache().join(SUMDB_NAME).join(lookup);
+        fs::create_dir_all(cache_file.parent().unwrap())?;
+        fs::write(&cache_file, "h1:...")?;
+        assert_eq!(status(&format!("sum.golang.org/{lookup}"))?, 200);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sumdb_concurrent() -> anyhow::Result<()> {
+        // a checksum database answering a lookup with its path.
+        let sumdb = Server::http("127.0.0.1:0").map_err(|e| anyhow!("{e}"))?;
+        let sumdb_url = format!("http://{}", sumdb.server_addr());
+        thread::spawn(move || {
+            for request in sumdb.incoming_requests() {
+                let body = request.url().to_owned();
+                let _ = request.respond(Response::from_string(body));
+            }
+        });
+        let tmp_dir = tempfile::tempdir()?;
+        let proxy = Arc::new(Proxy {
+            goup_home: Dir::new(tmp_dir.path()),
+            fetch: false,
+            synthesizing: Mutex::new(()),
+            sumdb_url,
+        });
+
+        let lookups: Vec<String> = ["linux-amd64", "darwin-arm64", "windows-386"]
+            .iter()
+            .map(|p| format!("lookup/golang.org/toolchain@v0.0.1-go1.21.9.{p}"))
+            .collect();
+        let handles: Vec<_> = lookups
+            .iter()
+            .flat_map(|lookup| [lookup.clone(), lookup.clone()])
+            .map(|lookup| {
+                let proxy = Arc::clone(&proxy);
+                thread::spawn(move || proxy.sumdb(&format!("{SUMDB_NAME}/{lookup}")))
+            })
+            .collect();
+        for handle in handles {
+            let response = handle.join().expect("no panic")?;
+            assert_eq!(response.status_code().0, 200);
+        }
+        let cache = proxy.goup_home.sumdb_cache().join(SUMDB_NAME);
+        for lookup in &lookups {
+            assert_eq!(
+                fs::read_to_string(cache.join(lookup))?,
+                format!("/{lookup}")
+            );
+        }
+        let dir = cache.join("lookup").join("golang.org");
+        assert_eq!(dir.read_dir()?.count(), lookups.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_toolchain() {
+        let t = Toolchain::from_module_version("v0.0.1-go1.21.9.linux-amd64").unwrap();
+        assert_eq!(t.version.to_string(), "1.21.9");
+        assert_eq!((t.os.as_str(), t.arch.as_str()), ("linux", "amd64"));
+        assert_eq!(t.archive(), "go1.21.9.linux-amd64.tar.gz");
+        assert_eq!(t.module_version(), "v0.0.1-go1.21.9.linux-amd64");
+
+        let t = Toolchain::from_archive("go1.21rc2.windows-386.zip").unwrap();
+        assert_eq!(t.module_version(), "v0.0.1-go1.21rc2.windows-386");
+        assert_eq!(t.archive(), "go1.21rc2.windows-386.zip");
+
+        assert_eq!(
+            Toolchain::from_module_version("v0.0.1-go1.21.0-rc2.linux-amd64"),
+            None
+        );
+        assert_eq!(
+            Toolchain::from_module_version("v0.0.2-go1.21.9.linux-amd64"),
+            None
+        );
+        assert_eq!(Toolchain::from_archive("go1.21.9.linux-amd64.msi"), None);
+        assert_eq!(Toolchain::from_archive("go1.21.9.src.tar.gz"), None);
+    }
+
+    #[test]
+    fn test_rfc3339() {
+        assert_eq!(rfc3339(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        let at = |secs| rfc3339(UNIX_EPOCH + Duration::from_secs(secs));
+        assert_eq!(at(1691519568), "2023-08-08T18:32:48Z");
+        assert_eq!(at(951782400), "2000-02-29T00:00:00Z");
+    }
+}
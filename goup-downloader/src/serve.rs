@@ -0,0 +1,459 @@
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::anyhow;
+use tiny_http::{Header, Method, Request, Response, ResponseBox, Server};
+
+use goup_misc::op::{self, GoRelease};
+use goup_misc::{Dir, GoVersion, progress};
+
+use crate::downloader::{download_archive, download_archive_sha256, verify_archive_file_sha256};
+use crate::utils;
+
+/// the release index is refetched from upstream when the last fetch is older than this.
+const RELEASE_INDEX_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// a Go download mirror serving the cached release index, archives and sha256 files, at
+/// the paths of the upstream host, so other goup instances point `GOUP_GO_HOST` at it
+/// and `GOUP_GO_DOWNLOAD_BASE_URL` at its `/dl`.
+pub struct MirrorServer {
+    server: Server,
+    mirror: Arc<Mirror>,
+}
+
+struct Mirror {
+    goup_home: Dir,
+    /// the upstream host the release index is fetched through, the archives are fetched
+    /// from `GOUP_GO_DOWNLOAD_BASE_URL`.
+    upstream: Option<String>,
+    /// when the release index was fetched from upstream last time.
+    fetched_index: Mutex<Option<Instant>>,
+    /// fetching through is serialized, so an archive is downloaded once.
+    fetching: Mutex<()>,
+}
+
+impl MirrorServer {
+    /// bind the mirror to the address, such as `0.0.0.0:8080`. the release index is fetched
+    /// through the upstream host when it is stale, an archive which is not cached is fetched
+    /// from `GOUP_GO_DOWNLOAD_BASE_URL`.
+    pub fn bind(listen: &str, upstream: Option<String>) -> anyhow::Result<Self> {
+        let server =
+            Server::http(listen).map_err(|e| anyhow!("Listening on {listen} failed: {e}"))?;
+        Ok(Self {
+            server,
+            mirror: Arc::new(Mirror {
+                goup_home: Dir::goup_home()?,
+                upstream,
+                fetched_index: Mutex::new(None),
+                fetching: Mutex::new(()),
+            }),
+        })
+    }
+
+    /// the address listened on, such as `127.0.0.1:8080`.
+    pub fn local_addr(&self) -> String {
+        self.server.server_addr().to_string()
+    }
+
+    /// serve the requests until the process is stopped.
+    pub fn run(self) -> anyhow::Result<()> {
+        serve(&self.server, self.mirror);
+        Ok(())
+    }
+}
+
+/// the routes of an http server.
+pub(crate) trait Route: Send + Sync + 'static {
+    fn route(&self, request: &Request) -> anyhow::Result<ResponseBox>;
+}
+
+/// serve the GET and HEAD requests with the routes, a thread per request.
+pub(crate) fn serve<R: Route>(server: &Server, routes: Arc<R>) {
+    for request in server.incoming_requests() {
+        let routes = Arc::clone(&routes);
+        thread::spawn(move || handle(&*routes, request));
+    }
+}
+
+fn handle<R: Route>(routes: &R, request: Request) {
+    let url = request.url().to_owned();
+    let method = request.method().clone();
+    let response = if matches!(method, Method::Get | Method::Head) {
+        routes.route(&request).unwrap_or_else(|e| {
+            log::warn!("{method} {url}: {e}");
+            text_response(500, &e.to_string())
+        })
+    } else {
+        text_response(405, "method not allowed")
+    };
+    log::info!("{method} {url} {}", response.status_code().0);
+    if let Err(e) = request.respond(response) {
+        log::debug!("responding {url} failed: {e}");
+    }
+}
+
+impl Route for Mirror {
+    fn route(&self, request: &Request) -> anyhow::Result<ResponseBox> {
+        let (path, query) = request.url().split_once('?').unwrap_or((request.url(), ""));
+        let param = |key: &str| {
+            query
+                .split('&')
+                .filter_map(|kv| kv.split_once('='))
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v)
+        };
+        match path {
+            "/dl" | "/dl/" if param("mode") == Some("json") => {
+                self.release_index(param("include") == Some("all"))
+            }
+            "/VERSION" => self.latest_version(),
+            _ => match path.strip_prefix("/dl/") {
+                Some(name) if is_cache_file_name(name) => self.cache_file(name, request.headers()),
+                _ => Ok(text_response(404, "not found")),
+            },
+        }
+    }
+}
+
+impl Mirror {
+    /// the release index like `/dl/?mode=json`, all releases if `include=all`, otherwise
+    /// the newest releases of the two newest stable minor releases.
+    fn release_index(&self, all: bool) -> anyhow::Result<ResponseBox> {
+        let Some(releases) = self.releases()? else {
+            return Ok(text_response(404, "no release index is cached"));
+        };
+        let releases = if all {
+            releases
+        } else {
+            let mut minors = Vec::new();
+            releases
+                .into_iter()
+                .filter(|r| {
+                    let Ok(ver) = r.version.trim_start_matches("go").parse::<GoVersion>() else {
+                        return false;
+                    };
+                    if !ver.is_stable() || minors.contains(&ver.minor_release()) {
+                        return false;
+                    }
+                    minors.push(ver.minor_release());
+                    minors.len() <= 2
+                })
+                .collect()
+        };
+        Ok(Response::from_data(serde_json::to_vec(&releases)?)
+            .with_header(content_type("application/json"))
+            .boxed())
+    }
+
+    /// the newest stable version like `/VERSION?m=text`, such as `go1.22.3`.
+    fn latest_version(&self) -> anyhow::Result<ResponseBox> {
+        let latest = self.releases()?.and_then(|releases| {
+            releases
+                .into_iter()
+                .find(|r| r.stable)
+                .map(|r| format!("go{}", r.version.trim_start_matches("go")))
+        });
+        Ok(match latest {
+            Some(latest) => text_response(200, &format!("{latest}\n")),
+            None => text_response(404, "no release index is cached"),
+        })
+    }
+
+    /// the cached release index, newest first with `go` prefix like upstream. it is
+    /// refetched from upstream if stale, the cached one is used if fetching fails.
+    fn releases(&self) -> anyhow::Result<Option<Vec<GoRelease>>> {
+        let release_index = self.goup_home.release_index();
+        if let Some(host) = &self.upstream {
+            let mut fetched_index = self
+                .fetched_index
+                .lock()
+                .map_err(|_| anyhow!("fetching the release index panicked"))?;
+            if fetched_index.is_none_or(|t| t.elapsed() > RELEASE_INDEX_TTL) {
+                // the fetched release index is cached by `list_upstream_go_releases`.
+                if let Err(e) = op::list_upstream_go_releases(host) {
+                    log::warn!("Fetching the release index from {host} failed: {e}");
+                }
+                *fetched_index = Some(Instant::now());
+            }
+        }
+        if !release_index.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&fs::read_to_string(
+            &release_index,
+        )?)?))
+    }
+
+    /// an archive or a sha256 file in the cache, fetched through on a miss if the archive
+    /// is in the release index.
+    fn cache_file(&self, name: &str, headers: &[Header]) -> anyhow::Result<ResponseBox> {
+        let file = self.goup_home.cache_file(name);
+        let archive = name.trim_end_matches(".sha256");
+        if !file.exists() && self.upstream.is_some() && self.is_released(archive)? {
+            self.fetch_through(archive)?;
+        }
+        if !file.exists() {
+            return Ok(text_response(404, "not found"));
+        }
+        let content_type = if name.ends_with(".sha256") {
+            "text/plain; charset=utf-8"
+        } else {
+            "application/octet-stream"
+        };
+        Ok(file_response(&file, headers)?.with_header(self::content_type(content_type)))
+    }
+
+    /// the archive is a file of a release in the release index.
+    fn is_released(&self, archive: &str) -> anyhow::Result<bool> {
+        Ok(self.releases()?.is_some_and(|releases| {
+            releases
+                .iter()
+                .flat_map(|r| &r.files)
+                .any(|f| f.filename == archive)
+        }))
+    }
+
+    /// download the archive and its sha256 into the cache, verified.
+    fn fetch_through(&self, archive: &str) -> anyhow::Result<()> {
+        let _guard = self
+            .fetching
+            .lock()
+            .map_err(|_| anyhow!("fetching through panicked"))?;
+        fetch_archive(&self.goup_home, archive)
+    }
+}
+
+/// download the archive and its sha256 into the cache from `GOUP_GO_DOWNLOAD_BASE_URL`,
+/// verified. it is skipped if they are cached already.
+pub(crate) fn fetch_archive(goup_home: &Dir, archive: &str) -> anyhow::Result<()> {
+    let cache = goup_home.cache();
+    let archive_file = cache.join_path(archive);
+    let sha256_file = cache.join_path(utils::archive_sha256(archive));
+    if archive_file.exists() && sha256_file.exists() {
+        return Ok(());
+    }
+    fs::create_dir_all(&cache)?;
+    let (archive_url, sha256_url) = utils::archive_url(archive);
+    let tmp_archive = cache.join_path(format!(".{archive}.download"));
+    let tmp_sha256 = cache.join_path(format!(".{archive}.sha256.download"));
+    log::info!("Fetching {archive_url}");
+    download_archive(progress::multi_progress(), &tmp_archive, &archive_url)?;
+    download_archive_sha256(&tmp_sha256, &sha256_url)?;
+    if !verify_archive_file_sha256(&tmp_archive, &tmp_sha256)? {
+        fs::remove_file(&tmp_archive)?;
+        fs::remove_file(&tmp_sha256)?;
+        anyhow::bail!("Hashsum NOT match {archive}");
+    }
+    fs::rename(&tmp_archive, &archive_file)?;
+    fs::rename(&tmp_sha256, &sha256_file)?;
+    Ok(())
+}
+
+/// a plain archive or sha256 file name, never a path out of the cache.
+fn is_cache_file_name(name: &str) -> bool {
+    !name.starts_with('.')
+        && !name.contains(['/', '\\'])
+        && (name.ends_with(".tar.gz") || name.ends_with(".zip") || name.ends_with(".sha256"))
+}
+
+/// the file, or the byte range of it if a single range is requested.
+pub(crate) fn file_response(path: &Path, headers: &[Header]) -> anyhow::Result<ResponseBox> {
+    let range = headers
+        .iter()
+        .find(|h| h.field.equiv("Range"))
+        .map(|h| h.value.as_str());
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let accept_ranges = header("Accept-Ranges", "bytes");
+    let Some(range) = range else {
+        return Ok(Response::from_file(file).with_header(accept_ranges).boxed());
+    };
+    match parse_range(range, len) {
+        Some(Ok((start, end))) => {
+            file.seek(SeekFrom::Start(start))?;
+            let size = end - start + 1;
+            Ok(Response::new(
+                206.into(),
+                vec![
+                    accept_ranges,
+                    header("Content-Range", &format!("bytes {start}-{end}/{len}")),
+                ],
+                Box::new(file.take(size)) as Box<dyn Read + Send>,
+                Some(size as usize),
+                None,
+            ))
+        }
+        Some(Err(())) => Ok(Response::empty(416)
+            .with_header(header("Content-Range", &format!("bytes */{len}")))
+            .boxed()),
+        // not a single byte range, the whole file is served.
+        None => Ok(Response::from_file(file).with_header(accept_ranges).boxed()),
+    }
+}
+
+/// parse a single byte range `bytes=start-end`, `bytes=start-` or `bytes=-suffix` into
+/// the inclusive range of a file of `len` bytes. `Err` if it is not satisfiable.
+fn parse_range(range: &str, len: u64) -> Option<Result<(u64, u64), ()>> {
+    let (start, end) = range.trim().strip_prefix("bytes=")?.split_once('-')?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => {
+            let suffix: u64 = suffix.parse().ok()?;
+            (len.saturating_sub(suffix), len.checked_sub(1))
+        }
+        (start, "") => (start.parse().ok()?, len.checked_sub(1)),
+        (start, end) => {
+            let (start, end): (u64, u64) = (start.parse().ok()?, end.parse().ok()?);
+            if end < start {
+                return None;
+            }
+            (start, Some(end.min(len.saturating_sub(1))))
+        }
+    };
+    Some(match end {
+        Some(end) if start < len => Ok((start, end)),
+        _ => Err(()),
+    })
+}
+
+pub(crate) fn text_response(status: u16, body: &str) -> ResponseBox {
+    Response::from_string(body)
+        .with_status_code(status)
+        .with_header(content_type("text/plain; charset=utf-8"))
+        .boxed()
+}
+
+pub(crate) fn content_type(value: &str) -> Header {
+    header("Content-Type", value)
+}
+
+fn header(field: &str, value: &str) -> Header {
+    Header::from_bytes(field.as_bytes(), value.as_bytes()).expect("valid header")
+}
+
+#[cfg(test)]
+mod tests {
+    use goup_misc::consts::GOUP_HOME;
+    use goup_misc::op::GoFile;
+    use reqwest::blocking::Client;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-9", 100), Some(Ok((0, 9))));
+        assert_eq!(parse_range("bytes=90-199", 100), Some(Ok((90, 99))));
+        assert_eq!(parse_range("bytes=10-", 100), Some(Ok((10, 99))));
+        assert_eq!(parse_range("bytes=-10", 100), Some(Ok((90, 99))));
+        assert_eq!(parse_range("bytes=-200", 100), Some(Ok((0, 99))));
+        assert_eq!(parse_range("bytes=100-199", 100), Some(Err(())));
+        assert_eq!(parse_range("bytes=0-", 0), Some(Err(())));
+        assert_eq!(parse_range("bytes=9-0", 100), None);
+        assert_eq!(parse_range("bytes=0-1,5-9", 100), None);
+        assert_eq!(parse_range("items=0-9", 100), None);
+
+        assert!(is_cache_file_name("go1.21.9.linux-amd64.tar.gz"));
+        assert!(is_cache_file_name("go1.21.9.windows-amd64.zip.sha256"));
+        assert!(!is_cache_file_name("../releases.json"));
+        assert!(!is_cache_file_name("../go1.21.9.linux-amd64.tar.gz"));
+        assert!(!is_cache_file_name(".go1.21.9.linux-amd64.tar.gz.download"));
+    }
+
+    fn release(version: &str, stable: bool, filename: &str) -> GoRelease {
+        GoRelease {
+            version: format!("go{version}"),
+            stable,
+            files: vec![GoFile {
+                arch: "amd64".to_owned(),
+                filename: filename.to_owned(),
+                kind: "archive".to_owned(),
+                os: "linux".to_owned(),
+                sha256: String::new(),
+                size: 0,
+                version: format!("go{version}"),
+            }],
+        }
+    }
+
+    /// a mirror of a GOUP_HOME with a release index and a cached archive.
+    fn bind(goup_home: &Dir, upstream: Option<String>) -> anyhow::Result<String> {
+        fs::create_dir_all(goup_home.cache())?;
+        let index = vec![
+            release("1.23rc1", false, "go1.23rc1.linux-amd64.tar.gz"),
+            release("1.22.3", true, "go1.22.3.linux-amd64.tar.gz"),
+            release("1.21.9", true, "go1.21.9.linux-amd64.tar.gz"),
+            release("1.20.14", true, "go1.20.14.linux-amd64.tar.gz"),
+        ];
+        fs::write(goup_home.release_index(), serde_json::to_vec(&index)?)?;
+        fs::write(
+            goup_home.cache_file("go1.21.9.linux-amd64.tar.gz"),
+            "0123456789",
+        )?;
+        let server = temp_env::with_var(GOUP_HOME, Some(goup_home.as_os_str()), || {
+            MirrorServer::bind("127.0.0.1:0", upstream)
+        })?;
+        let addr = format!("http://{}", server.local_addr());
+        thread::spawn(move || server.run());
+        Ok(addr)
+    }
+
+    fn versions(releases: &[GoRelease]) -> Vec<&str> {
+        releases.iter().map(|r| r.version.as_str()).collect()
+    }
+
+    #[test]
+    fn test_routes() -> anyhow::Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        let addr = bind(&Dir::new(tmp_dir.path()), None)?;
+        let client = Client::builder().no_proxy().build()?;
+
+        let all: Vec<GoRelease> = client
+            .get(format!("{addr}/dl/?mode=json&include=all"))
+            .send()?
+            .json()?;
+        assert_eq!(
+            versions(&all),
+            ["go1.23rc1", "go1.22.3", "go1.21.9", "go1.20.14"]
+        );
+        let newest: Vec<GoRelease> = client.get(format!("{addr}/dl/?mode=json")).send()?.json()?;
+        assert_eq!(versions(&newest), ["go1.22.3", "go1.21.9"]);
+        let latest = client.get(format!("{addr}/VERSION?m=text")).send()?;
+        assert_eq!(latest.text()?, "go1.22.3\n");
+
+        let archive = format!("{addr}/dl/go1.21.9.linux-amd64.tar.gz");
+        let resp = client.get(&archive).header("Range", "bytes=2-5").send()?;
+        assert_eq!(resp.status().as_u16(), 206);
+        assert_eq!(resp.headers()["Content-Range"], "bytes 2-5/10");
+        assert_eq!(resp.text()?, "2345");
+        let resp = client.get(&archive).header("Range", "bytes=10-").send()?;
+        assert_eq!(resp.status().as_u16(), 416);
+        assert_eq!(client.get(&archive).send()?.text()?, "0123456789");
+
+        let missing = client
+            .get(format!("{addr}/dl/go1.22.3.linux-amd64.tar.gz"))
+            .send()?;
+        assert_eq!(missing.status().as_u16(), 404);
+        let post = client.post(&archive).send()?;
+        assert_eq!(post.status().as_u16(), 405);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fetch_through_released_only() -> anyhow::Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        // nothing listens on the upstream, a fetch through fails with 500.
+        let addr = bind(
+            &Dir::new(tmp_dir.path()),
+            Some("http://127.0.0.1:1".to_owned()),
+        )?;
+        let client = Client::builder().no_proxy().build()?;
+        let resp = client
+            .get(format!("{addr}/dl/go9.9.9.linux-amd64.tar.gz"))
+            .send()?;
+        assert_eq!(resp.status().as_u16(), 404);
+        Ok(())
+    }
+}
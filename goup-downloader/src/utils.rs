@@ -3,6 +3,13 @@ use std::env;
 /// go_version_archive returns the zip or tar.gz of the given Go version.
 /// go1.21.5.linux-amd64.tar.gz, go1.21.5.windows-amd64.zip
 pub fn go_version_archive(version: &str) -> String {
+    let (os, arch) = platform();
+    let ext = if os == "windows" { "zip" } else { "tar.gz" };
+    format!("{version}.{os}-{arch}.{ext}")
+}
+
+/// platform returns the Go os and arch of the current platform, such as `linux`, `amd64`.
+pub fn platform() -> (&'static str, &'static str) {
     let os = match env::consts::OS {
         "macos" => "darwin",
         os => os,
@@ -14,8 +21,24 @@ pub fn go_version_archive(version: &str) -> String {
         (_, "aarch64") => "arm64",
         _ => env::consts::ARCH,
     };
-    let ext = if os == "windows" { "zip" } else { "tar.gz" };
-    format!("{version}.{os}-{arch}.{ext}")
+    (os, arch)
+}
+
+/// parse_platforms parses `{os}/{arch}`, such as `linux/amd64`, the current platform if empty.
+pub(crate) fn parse_platforms(platforms: &[String]) -> anyhow::Result<Vec<(String, String)>> {
+    if platforms.is_empty() {
+        let (os, arch) = platform();
+        return Ok(vec![(os.to_owned(), arch.to_owned())]);
+    }
+    platforms
+        .iter()
+        .map(|p| {
+            p.split_once('/')
+                .filter(|(os, arch)| !os.is_empty() && !arch.is_empty())
+                .map(|(os, arch)| (os.to_owned(), arch.to_owned()))
+                .ok_or_else(|| anyhow::anyhow!("Invalid platform '{p}', such as 'linux/amd64'."))
+        })
+        .collect()
 }
 
 /// archive_sha256 returns `{archive}.sha256`
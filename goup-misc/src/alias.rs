@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Dir;
+use crate::GoVersionReq;
+use crate::Toolchain;
+use crate::Version;
+
+/// named aliases of Go versions, such as `prod -> go1.21.9`.
+/// it is stored in `${GOUP_HOME}/aliases.json`.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Aliases(BTreeMap<String, String>);
+
+impl Aliases {
+    /// load aliases from `${GOUP_HOME}/aliases.json`, empty if it is not exist.
+    pub fn load(goup_home: &Dir) -> anyhow::Result<Self> {
+        let aliases_file = goup_home.aliases();
+        if !aliases_file.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&aliases_file)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// save aliases to `${GOUP_HOME}/aliases.json`.
+    pub fn save(&self, goup_home: &Dir) -> anyhow::Result<()> {
+        if !goup_home.exists() {
+            fs::create_dir_all(goup_home)?;
+        }
+        fs::write(goup_home.aliases(), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// the normalized version which the alias targets.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(String::as_str)
+    }
+
+    /// add or replace an alias, the version is normalized before stored.
+    pub fn set(&mut self, name: &str, version: &str) -> anyhow::Result<()> {
+        if name.is_empty()
+            || !name.starts_with(|c: char| c.is_ascii_alphabetic())
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+        {
+            anyhow::bail!("Invalid alias name '{name}', it must start with a letter.");
+        }
+        if name.starts_with("go")
+            || !matches!(name.parse()?, Toolchain::Version(_))
+            || name.parse::<GoVersionReq>().is_ok()
+        {
+            anyhow::bail!("Alias name '{name}' is reserved.");
+        }
+        if self.0.contains_key(version) {
+            anyhow::bail!("'{version}' is an alias, an alias can not target another alias.");
+        }
+        self.0.insert(name.to_owned(), Version::normalize(version));
+        Ok(())
+    }
+
+    /// remove an alias, returns the version it targeted.
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        self.0.remove(name)
+    }
+
+    /// names of the aliases which target the normalized version.
+    pub fn names_of(&self, version: &str) -> Vec<String> {
+        self.0
+            .iter()
+            .filter(|(_, target)| *target == version)
+            .map(|(name, _)| name.to_owned())
+            .collect()
+    }
+
+    /// iterate over `(name, version)` pairs, ordered by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aliases() -> anyhow::Result<()> {
+        let tmp_home_dir = tempfile::tempdir()?;
+        let goup_home = Dir::new(tmp_home_dir);
+        assert!(Aliases::load(&goup_home)?.is_empty());
+
+        let mut aliases = Aliases::default();
+        aliases.set("prod", "1.21.9")?;
+        aliases.set("edge", "go1.23rc2")?;
+        aliases.set("dev", "1.21.9")?;
+        assert_eq!(aliases.get("prod"), Some("go1.21.9"));
+        assert_eq!(aliases.get("edge"), Some("go1.23rc2"));
+        assert_eq!(aliases.get("1.21.9"), None);
+        assert_eq!(aliases.names_of("go1.21.9"), vec!["dev", "prod"]);
+        assert!(aliases.names_of("go1.23rc2").contains(&"edge".to_owned()));
+
+        aliases.save(&goup_home)?;
+        let loaded = Aliases::load(&goup_home)?;
+        assert_eq!(loaded, aliases);
+
+        assert_eq!(aliases.remove("dev"), Some("go1.21.9".to_owned()));
+        assert_eq!(aliases.remove("dev"), None);
+        assert_eq!(aliases.names_of("go1.21.9"), vec!["prod"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_aliases() {
+        let mut aliases = Aliases::default();
+        for name in [
+            "",
+            "1.21",
+            "a/b",
+            "stable",
+            "latest",
+            "oldstable",
+            "next",
+            "tip",
+            "gotip",
+            "go1",
+            "-x",
+        ] {
+            assert!(aliases.set(name, "1.21.9").is_err(), "{name}");
+        }
+        aliases.set("prod", "1.21.9").unwrap();
+        assert!(aliases.set("dev", "prod").is_err());
+    }
+}
@@ -0,0 +1,540 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::str::FromStr;
+use std::sync::LazyLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::anyhow;
+use regex::Regex;
+
+use crate::Dir;
+use crate::consts;
+use crate::op;
+
+/// go1.21.5.linux-amd64.tar.gz, go1.21.5.windows-amd64.zip
+static ARCHIVE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(go\d[^-]*)\.[a-z0-9]+-[a-z0-9]+\.(tar\.gz|zip)$").expect("valid regex")
+});
+
+/// an archive in `${GOUP_HOME}/cache`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheEntry {
+    /// archive filename, such as `go1.21.5.linux-amd64.tar.gz`.
+    pub archive: String,
+    /// normalized version, such as `go1.21.5`.
+    pub version: String,
+    /// size in bytes of the archive and its `.sha256` file.
+    pub size: u64,
+    /// last modified time of the archive.
+    pub modified: SystemTime,
+    /// the version of the archive is installed.
+    pub installed: bool,
+}
+
+impl CacheEntry {
+    /// time elapsed since the archive was downloaded.
+    pub fn age(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(self.modified)
+            .unwrap_or_default()
+    }
+}
+
+/// the version of an archive filename, `None` if it is not a Go archive.
+pub fn archive_version(archive: &str) -> Option<&str> {
+    ARCHIVE_RE
+        .captures(archive)
+        .and_then(|c| c.get(1))
+        .map(|m| m.as_str())
+}
+
+/// list the archives in cache, oldest first.
+pub fn list() -> anyhow::Result<Vec<CacheEntry>> {
+    let goup_home = Dir::goup_home()?;
+    let mut entries = Vec::new();
+    for archive in op::list_cache(false)? {
+        let Some(version) = archive_version(&archive) else {
+            continue;
+        };
+        let metadata = fs::metadata(goup_home.cache_file(&archive))?;
+        let sha256_size =
+            fs::metadata(goup_home.cache_file(format!("{archive}.sha256"))).map_or(0, |m| m.len());
+        entries.push(CacheEntry {
+            version: version.to_owned(),
+            size: metadata.len() + sha256_size,
+            modified: metadata.modified()?,
+            installed: goup_home.is_dot_unpacked_success_file_exists(version),
+            archive,
+        });
+    }
+    entries.sort_by_key(|e| e.modified);
+    Ok(entries)
+}
+
+/// remove the archive, its `.sha256` file and the toolchain module zip synthesized from it.
+pub fn remove(entry: &CacheEntry) -> anyhow::Result<()> {
+    let goup_home = Dir::goup_home()?;
+    fs::remove_file(goup_home.cache_file(&entry.archive))?;
+    let sha256_file = goup_home.cache_file(format!("{}.sha256", entry.archive));
+    if sha256_file.exists() {
+        fs::remove_file(sha256_file)?;
+    }
+    let stem = entry
+        .archive
+        .strip_suffix(".tar.gz")
+        .or_else(|| entry.archive.strip_suffix(".zip"))
+        .unwrap_or(&entry.archive);
+    remove_toolchain_modules(&goup_home, &format!("v0.0.1-{stem}.zip"))
+}
+
+/// remove the `golang.org/toolchain` module zips synthesized by `goup proxy` whose names
+/// start with the prefix, such as `v0.0.1-go1.21.5.`. a zip whose archive is still cached is
+/// kept, it was synthesized from the archive, not from a removed version.
+pub(crate) fn remove_toolchain_modules(goup_home: &Dir, prefix: &str) -> anyhow::Result<()> {
+    let dir = goup_home.toolchain_modules();
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in dir.read_dir()? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(stem) = name
+            .strip_prefix("v0.0.1-")
+            .and_then(|n| n.strip_suffix(".zip"))
+        else {
+            continue;
+        };
+        let cached = [".tar.gz", ".zip"]
+            .iter()
+            .any(|ext| goup_home.cache_file(format!("{stem}{ext}")).is_file());
+        if name.starts_with(prefix) && !cached {
+            fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// which archives are kept by `goup cache prune`, the others are removed.
+/// the textual form is comma separated, such as `keep-installed,older-than=30d,max-size=2G`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PrunePolicy {
+    /// keep the archives whose version is installed.
+    pub keep_installed: bool,
+    /// only remove the archives older than it.
+    pub older_than: Option<Duration>,
+    /// remove the oldest archives until the cache is not larger than it.
+    pub max_size: Option<u64>,
+}
+
+impl FromStr for PrunePolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut policy = Self::default();
+        for item in s.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            match item.split_once('=') {
+                None if item == "keep-installed" => policy.keep_installed = true,
+                Some(("older-than", v)) => policy.older_than = Some(parse_duration(v)?),
+                Some(("max-size", v)) => policy.max_size = Some(parse_size(v)?),
+                _ => return Err(anyhow!("invalid cache prune policy: {item}")),
+            }
+        }
+        Ok(policy)
+    }
+}
+
+impl PrunePolicy {
+    /// the archives to remove, oldest first. entries must be oldest first.
+    pub fn select<'a>(&self, entries: &'a [CacheEntry]) -> Vec<&'a CacheEntry> {
+        let candidates: Vec<_> = entries
+            .iter()
+            .filter(|e| !(self.keep_installed && e.installed))
+            .collect();
+        if self.older_than.is_none() && self.max_size.is_none() {
+            return candidates;
+        }
+        let mut selected: Vec<_> = match self.older_than {
+            Some(older_than) => candidates
+                .iter()
+                .filter(|e| e.age() > older_than)
+                .copied()
+                .collect(),
+            None => Vec::new(),
+        };
+        if let Some(max_size) = self.max_size {
+            let mut size: u64 = entries.iter().map(|e| e.size).sum::<u64>()
+                - selected.iter().map(|e| e.size).sum::<u64>();
+            for e in candidates {
+                if size <= max_size {
+                    break;
+                }
+                if !selected.contains(&e) {
+                    size -= e.size;
+                    selected.push(e);
+                }
+            }
+            selected.sort_by_key(|e| e.modified);
+        }
+        selected
+    }
+}
+
+/// remove the archives selected by the policy, returns the removed ones.
+pub fn prune(policy: &PrunePolicy) -> anyhow::Result<Vec<CacheEntry>> {
+    let entries = list()?;
+    let selected = policy.select(&entries);
+    for e in &selected {
+        remove(e)?;
+    }
+    Ok(selected.into_iter().cloned().collect())
+}
+
+/// the shared, content-addressed archive cache of `GOUP_CACHE_DIR`, such as a group-writable
+/// directory on a build farm. archives are stored as `sha256/{sha256}` and written by an atomic
+/// rename, so concurrent readers never see a partial file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedCache {
+    root: PathBuf,
+}
+
+impl SharedCache {
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+        }
+    }
+
+    /// the shared cache if `GOUP_CACHE_DIR` is set.
+    pub fn from_env() -> Option<Self> {
+        env::var(consts::GOUP_CACHE_DIR)
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(Self::new)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// the path of the archive whose sha256 is given.
+    pub fn blob(&self, sha256: &str) -> anyhow::Result<PathBuf> {
+        if sha256.len() != 64 || !sha256.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(anyhow!("invalid sha256: {sha256}"));
+        }
+        Ok(self.root.join("sha256").join(sha256.to_ascii_lowercase()))
+    }
+
+    /// hard link, or copy across filesystems, the archive to dest. `false` if it isn't cached.
+    pub fn fetch(&self, sha256: &str, dest: &Path) -> anyhow::Result<bool> {
+        let blob = self.blob(sha256)?;
+        if !blob.is_file() {
+            return Ok(false);
+        }
+        let tmp = tmp_path(dest);
+        if fs::hard_link(&blob, &tmp).is_err() {
+            fs::copy(&blob, &tmp)?;
+        }
+        fs::rename(&tmp, dest)?;
+        Ok(true)
+    }
+
+    /// remove the archive from the shared cache, such as one that failed verification.
+    pub fn evict(&self, sha256: &str) -> anyhow::Result<()> {
+        let blob = self.blob(sha256)?;
+        if blob.exists() {
+            fs::remove_file(&blob)?;
+        }
+        Ok(())
+    }
+
+    /// copy the verified archive into the shared cache, a no-op if it is cached already.
+    pub fn store(&self, src: &Path, sha256: &str) -> anyhow::Result<()> {
+        let blob = self.blob(sha256)?;
+        if blob.exists() {
+            return Ok(());
+        }
+        if let Some(dir) = blob.parent()
+            && !dir.exists()
+        {
+            fs::create_dir_all(dir)?;
+            set_shared_permissions(dir, 0o2775);
+        }
+        let tmp = tmp_path(&blob);
+        fs::copy(src, &tmp)?;
+        // read-only, an in-place write through a hard link fails instead of corrupting it.
+        set_shared_permissions(&tmp, 0o444);
+        if let Err(e) = fs::rename(&tmp, &blob) {
+            fs::remove_file(&tmp)?;
+            // another writer stored the same archive first.
+            if !blob.exists() {
+                return Err(e.into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// a sibling temporary path which is unique across processes.
+pub fn tmp_path(path: &Path) -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.subsec_nanos());
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}.{nanos}.tmp", process::id()));
+    path.with_file_name(name)
+}
+
+/// let the other users of the group share the file, it is best effort.
+fn set_shared_permissions(path: &Path, mode: u32) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Err(e) = fs::set_permissions(path, fs::Permissions::from_mode(mode)) {
+            log::debug!("set permissions of {}: {e}", path.display());
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = (path, mode);
+}
+
+/// parse a duration like `30d`, `12h`, `45m`, `10s` or `2w`.
+pub fn parse_duration(s: &str) -> anyhow::Result<Duration> {
+    let s = s.trim();
+    let (num, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()));
+    let num: u64 = num.parse().map_err(|_| anyhow!("invalid duration: {s}"))?;
+    let secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "" | "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => {
+            return Err(anyhow!(
+                "invalid duration unit: {s}, expect s, m, h, d or w"
+            ));
+        }
+    };
+    num.checked_mul(secs)
+        .map(Duration::from_secs)
+        .ok_or_else(|| anyhow!("duration is too long: {s}"))
+}
+
+/// parse a size like `2G`, `500M`, `1.5GiB` or `1024`(bytes).
+pub fn parse_size(s: &str) -> anyhow::Result<u64> {
+    let s = s.trim();
+    let (num, unit) = s.split_at(
+        s.find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(s.len()),
+    );
+    let num: f64 = num.parse().map_err(|_| anyhow!("invalid size: {s}"))?;
+    let unit = match unit.to_ascii_uppercase().trim_end_matches("IB") {
+        "" | "B" => 1u64,
+        "K" | "KB" => 1 << 10,
+        "M" | "MB" => 1 << 20,
+        "G" | "GB" => 1 << 30,
+        "T" | "TB" => 1 << 40,
+        _ => return Err(anyhow!("invalid size unit: {s}, expect K, M, G or T")),
+    };
+    Ok((num * unit as f64) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(version: &str, size: u64, days: u64, installed: bool) -> CacheEntry {
+        CacheEntry {
+            archive: format!("{version}.linux-amd64.tar.gz"),
+            version: version.to_owned(),
+            size,
+            modified: SystemTime::now()
+                .checked_sub(Duration::from_secs(days * 24 * 60 * 60))
+                .unwrap_or(SystemTime::UNIX_EPOCH),
+            installed,
+        }
+    }
+
+    #[test]
+    fn test_shared_cache() -> anyhow::Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        let shared = SharedCache::new(tmp_dir.path().join("shared"));
+        let sha256 = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        let src = tmp_dir.path().join("go1.21.5.linux-amd64.tar.gz");
+        let dest = tmp_dir.path().join("dest.tar.gz");
+        fs::write(&src, "abc")?;
+
+        assert!(shared.blob("../etc/passwd").is_err());
+        assert!(!shared.fetch(sha256, &dest)?);
+        assert!(!dest.exists());
+
+        shared.store(&src, sha256)?;
+        // storing twice is a no-op
+        shared.store(&src, sha256)?;
+        let blob = shared.blob(sha256)?;
+        assert_eq!(fs::read_to_string(&blob)?, "abc");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&blob)?.permissions().mode() & 0o777, 0o444);
+        }
+
+        assert!(shared.fetch(sha256, &dest)?);
+        assert_eq!(fs::read_to_string(&dest)?, "abc");
+        let leftovers = fs::read_dir(blob.parent().unwrap())?.count();
+        assert_eq!(leftovers, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_remove() -> anyhow::Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        let goup_home = Dir::new(tmp_dir.path());
+        let modules = goup_home.toolchain_modules();
+        fs::create_dir_all(&modules)?;
+        let entry = entry("go1.21.5", 3, 0, false);
+        fs::write(goup_home.cache_file(&entry.archive), "abc")?;
+        fs::write(
+            goup_home.cache_file(format!("{}.sha256", entry.archive)),
+            "",
+        )?;
+        let module_zip = modules.join("v0.0.1-go1.21.5.linux-amd64.zip");
+        let other_zip = modules.join("v0.0.1-go1.21.5.darwin-arm64.zip");
+        fs::write(&module_zip, "")?;
+        fs::write(&other_zip, "")?;
+
+        temp_env::with_var(consts::GOUP_HOME, Some(goup_home.as_os_str()), || {
+            remove(&entry)
+        })?;
+        assert!(!goup_home.cache_file(&entry.archive).exists());
+        assert!(!module_zip.exists());
+        assert!(other_zip.exists());
+
+        // removing the installed go1.21.5 keeps the zip of the cached darwin archive.
+        fs::write(goup_home.cache_file("go1.21.5.darwin-arm64.tar.gz"), "abc")?;
+        fs::write(&module_zip, "")?;
+        remove_toolchain_modules(&goup_home, "v0.0.1-go1.21.5.")?;
+        assert!(!module_zip.exists());
+        assert!(other_zip.exists());
+
+        fs::remove_file(goup_home.cache_file("go1.21.5.darwin-arm64.tar.gz"))?;
+        remove_toolchain_modules(&goup_home, "v0.0.1-go1.21.5.")?;
+        assert!(!other_zip.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_archive_version() {
+        assert_eq!(
+            archive_version("go1.21.5.linux-amd64.tar.gz"),
+            Some("go1.21.5")
+        );
+        assert_eq!(
+            archive_version("go1.22rc1.windows-amd64.zip"),
+            Some("go1.22rc1")
+        );
+        assert_eq!(
+            archive_version("go1.20.linux-armv6l.tar.gz"),
+            Some("go1.20")
+        );
+        assert_eq!(archive_version("go1.21.5.linux-amd64.tar.gz.sha256"), None);
+        assert_eq!(archive_version("readme.txt"), None);
+    }
+
+    #[test]
+    fn test_parse() -> anyhow::Result<()> {
+        assert_eq!(parse_duration("30d")?, Duration::from_secs(30 * 86400));
+        assert_eq!(parse_duration("12h")?, Duration::from_secs(12 * 3600));
+        assert_eq!(parse_duration("2w")?, Duration::from_secs(14 * 86400));
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("3y").is_err());
+        assert!(parse_duration(&format!("{}w", u64::MAX)).is_err());
+        assert!(parse_duration("99999999999999999999d").is_err());
+        assert_eq!(parse_size("2G")?, 2 << 30);
+        assert_eq!(parse_size("500MB")?, 500 << 20);
+        assert_eq!(parse_size("1.5GiB")?, 3 << 29);
+        assert_eq!(parse_size("1024")?, 1024);
+        assert!(parse_size("2X").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune_policy_from_str() -> anyhow::Result<()> {
+        assert_eq!(
+            "keep-installed, older-than=30d,max-size=2G".parse::<PrunePolicy>()?,
+            PrunePolicy {
+                keep_installed: true,
+                older_than: Some(Duration::from_secs(30 * 86400)),
+                max_size: Some(2 << 30),
+            }
+        );
+        assert_eq!("".parse::<PrunePolicy>()?, PrunePolicy::default());
+        assert!("keep-all".parse::<PrunePolicy>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune_policy_select() {
+        let entries = [
+            entry("go1.20", 100, 90, false),
+            entry("go1.21.0", 100, 60, true),
+            entry("go1.21.5", 100, 10, false),
+            entry("go1.22.0", 100, 1, true),
+        ];
+        let versions = |policy: PrunePolicy| -> Vec<String> {
+            policy
+                .select(&entries)
+                .into_iter()
+                .map(|e| e.version.clone())
+                .collect()
+        };
+
+        assert_eq!(versions(PrunePolicy::default()).len(), 4);
+        assert_eq!(
+            versions(PrunePolicy {
+                keep_installed: true,
+                ..Default::default()
+            }),
+            ["go1.20", "go1.21.5"]
+        );
+        assert_eq!(
+            versions(PrunePolicy {
+                older_than: Some(Duration::from_secs(30 * 86400)),
+                ..Default::default()
+            }),
+            ["go1.20", "go1.21.0"]
+        );
+        assert_eq!(
+            versions(PrunePolicy {
+                keep_installed: true,
+                older_than: Some(Duration::from_secs(30 * 86400)),
+                ..Default::default()
+            }),
+            ["go1.20"]
+        );
+        assert_eq!(
+            versions(PrunePolicy {
+                max_size: Some(250),
+                ..Default::default()
+            }),
+            ["go1.20", "go1.21.0"]
+        );
+        assert_eq!(
+            versions(PrunePolicy {
+                keep_installed: true,
+                max_size: Some(150),
+                ..Default::default()
+            }),
+            ["go1.20", "go1.21.5"]
+        );
+        assert_eq!(
+            versions(PrunePolicy {
+                keep_installed: true,
+                older_than: Some(Duration::from_secs(30 * 86400)),
+                max_size: Some(300),
+            }),
+            ["go1.20"]
+        );
+    }
+}
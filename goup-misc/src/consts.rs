@@ -1,12 +1,27 @@
 use std::env;
+use std::sync::OnceLock;
 
 pub const GOUP_HOME: &str = "GOUP_HOME";
 pub const GOUP_GO_HOST: &str = "GOUP_GO_HOST";
 pub const GOUP_GO_DOWNLOAD_BASE_URL: &str = "GOUP_GO_DOWNLOAD_BASE_URL";
+pub const GOUP_GO_VERSION: &str = "GOUP_GO_VERSION";
+pub const GOUP_LOG: &str = "GOUP_LOG";
+/// cache prune policy applied after a successful install, such as `keep-installed,max-size=2G`.
+pub const GOUP_CACHE_PRUNE: &str = "GOUP_CACHE_PRUNE";
+/// shared, content-addressed archive cache across users and `GOUP_HOME`s.
+pub const GOUP_CACHE_DIR: &str = "GOUP_CACHE_DIR";
+/// workspace roots searched for `.go-version` files by `goup prune`, separated like `PATH`.
+pub const GOUP_WORKSPACES: &str = "GOUP_WORKSPACES";
+
+/// project file which pins the Go version of a directory tree.
+pub const GO_VERSION_FILE: &str = ".go-version";
 
 pub const GO_HOST: &str = "https://golang.google.cn"; // "https://go.dev"; //
 pub const GO_DOWNLOAD_BASE_URL: &str = "https://dl.google.com/go";
 
+/// the download base URL of the process, such as the mirror of `goup.toml`.
+static DOWNLOAD_BASE_URL: OnceLock<String> = OnceLock::new();
+
 #[inline]
 fn get_var_or_else(key: &str, val: &str) -> String {
     if let Ok(s) = env::var(key)
@@ -17,12 +32,28 @@ fn get_var_or_else(key: &str, val: &str) -> String {
     val.to_string()
 }
 
+/// the environment variable is set and not empty.
+pub fn is_var_set(key: &str) -> bool {
+    env::var(key).is_ok_and(|s| !s.is_empty())
+}
+
 pub fn go_host() -> String {
     get_var_or_else(GOUP_GO_HOST, GO_HOST)
 }
 
 pub fn go_download_base_url() -> String {
-    get_var_or_else(GOUP_GO_DOWNLOAD_BASE_URL, GO_DOWNLOAD_BASE_URL)
+    get_var_or_else(
+        GOUP_GO_DOWNLOAD_BASE_URL,
+        DOWNLOAD_BASE_URL
+            .get()
+            .map_or(GO_DOWNLOAD_BASE_URL, String::as_str),
+    )
+}
+
+/// set the download base URL used when `GOUP_GO_DOWNLOAD_BASE_URL` is not set,
+/// only the first call takes effect.
+pub fn set_go_download_base_url(url: &str) {
+    let _ = DOWNLOAD_BASE_URL.set(url.trim_end_matches('/').to_owned());
 }
 
 #[cfg(test)]
@@ -34,6 +65,7 @@ mod tests {
         temp_env::with_vars_unset([GOUP_GO_HOST, GOUP_GO_DOWNLOAD_BASE_URL], || {
             assert_eq!(go_host(), GO_HOST);
             assert_eq!(go_download_base_url(), GO_DOWNLOAD_BASE_URL);
+            assert!(!is_var_set(GOUP_GO_HOST));
         })
     }
 
@@ -49,6 +81,7 @@ mod tests {
             || {
                 assert_eq!(go_host(), test_go_host);
                 assert_eq!(go_download_base_url(), test_go_download_base_url);
+                assert!(is_var_set(GOUP_GO_HOST));
             },
         )
     }
@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cache::tmp_path;
+use crate::manifest::Manifest;
+use crate::progress;
+use crate::{Dir, Version, op};
+
+/// the result of `goup dedupe`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DedupeReport {
+    /// files replaced by a hard link, or copied back by `--undo`.
+    pub files: usize,
+    /// bytes saved by the hard links, or taken back by `--undo`.
+    pub bytes: u64,
+}
+
+/// hard link the files with identical content and permissions across the installed versions.
+/// removing a version stays safe, the other versions keep their links to the content.
+pub fn dedupe_go_versions(dry_run: bool) -> anyhow::Result<DedupeReport> {
+    ensure_supported()?;
+    let goup_home = Dir::goup_home()?;
+    let sp = progress::spinner();
+    let mut report = DedupeReport::default();
+    let mut seen: HashMap<(String, u64), PathBuf> = HashMap::new();
+    for version in op::list_managed_go_versions()? {
+        sp.set_message(format!("Hashing {version}"));
+        let root = goup_home.version_go(&version);
+        // hash the files on disk, the recorded manifest may be stale.
+        let manifest = Manifest::build(&root)?;
+        for (rel, entry) in manifest.iter() {
+            if entry.size == 0 {
+                continue;
+            }
+            let path = root.join(rel);
+            let original = match seen.entry((entry.sha256.clone(), entry.size)) {
+                Entry::Vacant(v) => {
+                    v.insert(path);
+                    continue;
+                }
+                Entry::Occupied(o) => o.into_mut(),
+            };
+            if is_same_file(original, &path)? || !is_same_permissions(original, &path)? {
+                continue;
+            }
+            if !dry_run {
+                let tmp = tmp_path(&path);
+                if let Err(e) = fs::hard_link(&*original, &tmp) {
+                    // such as across filesystems.
+                    log::debug!("hard link {}: {e}", path.display());
+                    continue;
+                }
+                fs::rename(&tmp, &path)?;
+            }
+            report.files += 1;
+            report.bytes += entry.size;
+        }
+    }
+    sp.finish_and_clear();
+    Ok(report)
+}
+
+/// copy back the hard linked files of the version, so it owns all of its files again.
+pub fn undedupe_go_version(version: &str) -> anyhow::Result<DedupeReport> {
+    ensure_supported()?;
+    let version = Version::resolve(version)?;
+    let goup_home = Dir::goup_home()?;
+    if !goup_home.is_dot_unpacked_success_file_exists(&version)
+        || goup_home.is_dot_linked_file_exists(&version)
+    {
+        anyhow::bail!("{version} is not installed by goup.");
+    }
+    let mut report = DedupeReport::default();
+    undedupe_dir(&goup_home.version_go(&version), &mut report)?;
+    Ok(report)
+}
+
+fn undedupe_dir(dir: &Path, report: &mut DedupeReport) -> anyhow::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let path = entry.path();
+        if file_type.is_dir() {
+            undedupe_dir(&path, report)?;
+        } else if file_type.is_file() && link_count(&path)? > 1 {
+            let tmp = tmp_path(&path);
+            // fs::copy keeps the permissions.
+            let size = fs::copy(&path, &tmp)?;
+            fs::rename(&tmp, &path)?;
+            report.files += 1;
+            report.bytes += size;
+        }
+    }
+    Ok(())
+}
+
+/// the undedupe of all installed versions.
+pub fn undedupe_go_versions() -> anyhow::Result<DedupeReport> {
+    ensure_supported()?;
+    let mut report = DedupeReport::default();
+    for version in op::list_managed_go_versions()? {
+        let r = undedupe_go_version(&version)?;
+        report.files += r.files;
+        report.bytes += r.bytes;
+    }
+    Ok(report)
+}
+
+/// the file identity and the link count, which tell the hard links apart, are only
+/// available on unix without unsafe code.
+fn ensure_supported() -> anyhow::Result<()> {
+    if cfg!(unix) {
+        return Ok(());
+    }
+    anyhow::bail!("`goup dedupe` is only supported on unix.")
+}
+
+#[cfg(unix)]
+fn is_same_file(a: &Path, b: &Path) -> anyhow::Result<bool> {
+    use std::os::unix::fs::MetadataExt;
+    let (a, b) = (fs::metadata(a)?, fs::metadata(b)?);
+    Ok(a.dev() == b.dev() && a.ino() == b.ino())
+}
+
+#[cfg(not(unix))]
+fn is_same_file(_a: &Path, _b: &Path) -> anyhow::Result<bool> {
+    unreachable!("dedupe is only supported on unix")
+}
+
+fn is_same_permissions(a: &Path, b: &Path) -> anyhow::Result<bool> {
+    Ok(fs::metadata(a)?.permissions() == fs::metadata(b)?.permissions())
+}
+
+#[cfg(unix)]
+fn link_count(path: &Path) -> anyhow::Result<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Ok(fs::metadata(path)?.nlink())
+}
+
+#[cfg(not(unix))]
+fn link_count(_path: &Path) -> anyhow::Result<u64> {
+    unreachable!("dedupe is only supported on unix")
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn install(goup_home: &Dir, version: &str, files: &[(&str, &str)]) -> anyhow::Result<()> {
+        let root = goup_home.version_go(version);
+        for (rel, content) in files {
+            let path = root.join(rel);
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(path, content)?;
+        }
+        goup_home.create_dot_unpacked_success_file(version)
+    }
+
+    #[test]
+    fn test_dedupe() -> anyhow::Result<()> {
+        let tmp_home_dir = tempfile::tempdir()?;
+        let goup_home = Dir::new(tmp_home_dir);
+        install(
+            &goup_home,
+            "go1.21.0",
+            &[
+                ("VERSION", "go1.21.0"),
+                ("src/fmt/print.go", "fmt"),
+                ("empty", ""),
+            ],
+        )?;
+        install(
+            &goup_home,
+            "go1.21.1",
+            &[
+                ("VERSION", "go1.21.1"),
+                ("src/fmt/print.go", "fmt"),
+                ("empty", ""),
+            ],
+        )?;
+        let print_go = |v: &str| goup_home.version_go(v).join("src/fmt/print.go");
+
+        temp_env::with_var(
+            crate::consts::GOUP_HOME,
+            Some(goup_home.as_os_str()),
+            || {
+                let expected = DedupeReport { files: 1, bytes: 3 };
+                assert_eq!(dedupe_go_versions(true)?, expected);
+                assert!(!is_same_file(&print_go("go1.21.0"), &print_go("go1.21.1"))?);
+
+                assert_eq!(dedupe_go_versions(false)?, expected);
+                assert!(is_same_file(&print_go("go1.21.0"), &print_go("go1.21.1"))?);
+                // already linked
+                assert_eq!(dedupe_go_versions(false)?, DedupeReport::default());
+
+                assert_eq!(undedupe_go_version("1.21.1")?, expected);
+                assert!(!is_same_file(&print_go("go1.21.0"), &print_go("go1.21.1"))?);
+                assert_eq!(fs::read_to_string(print_go("go1.21.1"))?, "fmt");
+                anyhow::Ok(())
+            },
+        )
+    }
+}
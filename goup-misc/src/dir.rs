@@ -48,6 +48,14 @@ impl Dir {
     pub fn env(&self) -> Self {
         self.join_path("env")
     }
+    /// Extends `self` with `aliases.json`
+    pub fn aliases(&self) -> Self {
+        self.join_path("aliases.json")
+    }
+    /// Extends `self` with `releases.json`, the cached upstream release index.
+    pub fn release_index(&self) -> Self {
+        self.join_path("releases.json")
+    }
     /// Extends `self` with `current`.
     pub fn current(&self) -> Self {
         self.join_path("current")
@@ -76,6 +84,18 @@ impl Dir {
         d.push(p);
         d
     }
+    /// Extends `self` with `cache/toolchain`, the `golang.org/toolchain` module zips.
+    pub fn toolchain_modules(&self) -> Self {
+        let mut d = self.join_path("cache");
+        d.push("toolchain");
+        d
+    }
+    /// Extends `self` with `cache/sumdb`, the responses of the checksum database.
+    pub fn sumdb_cache(&self) -> Self {
+        let mut d = self.join_path("cache");
+        d.push("sumdb");
+        d
+    }
     /// Extends `self` with `{version}/go`
     pub fn version_go<P: AsRef<Path>>(&self, ver: P) -> Self {
         let mut d = self.join_path(ver);
@@ -108,6 +128,68 @@ impl Dir {
         File::create(&dot_unpacked_success_file)?;
         Ok(())
     }
+    /// Extends `self` with `{version}/.manifest`, the files recorded at install time.
+    pub fn version_dot_manifest<P: AsRef<Path>>(&self, ver: P) -> Self {
+        let mut d = self.join_path(ver);
+        d.push(".manifest");
+        d
+    }
+    /// Extends `self` with `{version}/.linked`
+    fn version_dot_linked<P: AsRef<Path>>(&self, ver: P) -> Self {
+        let mut d = self.join_path(ver);
+        d.push(".linked");
+        d
+    }
+    /// `${path}/.goup/{version}/.linked` is exist.
+    pub fn is_dot_linked_file_exists<P>(&self, ver: P) -> bool
+    where
+        P: AsRef<Path>,
+    {
+        self.version_dot_linked(&ver).exists()
+    }
+    /// read the linked GOROOT from `${path}/.goup/{version}/.linked` file
+    pub fn read_dot_linked_file<P>(&self, ver: P) -> Option<PathBuf>
+    where
+        P: AsRef<Path>,
+    {
+        fs::read_to_string(self.version_dot_linked(&ver))
+            .ok()
+            .and_then(|s| s.lines().next().map(|l| PathBuf::from(l.trim_end())))
+    }
+    /// read the Go version recorded at link time from `${path}/.goup/{version}/.linked` file,
+    /// such as `go1.21.5` or `devel go1.23-e8ee1dc`.
+    pub fn read_dot_linked_version<P>(&self, ver: P) -> Option<String>
+    where
+        P: AsRef<Path>,
+    {
+        fs::read_to_string(self.version_dot_linked(&ver))
+            .ok()
+            .and_then(|s| s.lines().nth(1).map(|l| l.trim().to_owned()))
+            .filter(|v| !v.is_empty())
+    }
+    /// create `${path}/.goup/{version}/.linked` file, which records the linked GOROOT
+    /// and its Go version.
+    pub fn create_dot_linked_file<P1, P2>(
+        &self,
+        ver: P1,
+        goroot: P2,
+        go_version: &str,
+    ) -> anyhow::Result<()>
+    where
+        P1: AsRef<Path>,
+        P2: AsRef<Path>,
+    {
+        let dot_linked_file = self.version_dot_linked(&ver);
+        let parent = dot_linked_file.parent();
+        if let Some(parent) = parent {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(
+            &dot_linked_file,
+            format!("{}\n{go_version}\n", goroot.as_ref().to_string_lossy()),
+        )?;
+        Ok(())
+    }
 }
 
 impl AsRef<Path> for Dir {
@@ -159,6 +241,14 @@ mod tests {
             Dir::new(home_dir).env().as_ref(),
             Path::new("/home/dev/.goup/env")
         );
+        assert_eq!(
+            Dir::new(home_dir).aliases().as_ref(),
+            Path::new("/home/dev/.goup/aliases.json")
+        );
+        assert_eq!(
+            Dir::new(home_dir).release_index().as_ref(),
+            Path::new("/home/dev/.goup/releases.json")
+        );
         assert_eq!(
             Dir::new(home_dir).current().as_ref(),
             Path::new("/home/dev/.goup/current")
@@ -207,4 +297,33 @@ mod tests {
         assert!(tmp_goup_home.is_dot_unpacked_success_file_exists("go1.21.2"));
         Ok(())
     }
+
+    #[test]
+    fn test_dot_linked_file() -> anyhow::Result<()> {
+        let tmp_home_dir = tempfile::tempdir()?;
+        let tmp_goup_home = Dir::new(tmp_home_dir);
+        assert!(!tmp_goup_home.is_dot_linked_file_exists("gosystem"));
+        assert_eq!(tmp_goup_home.read_dot_linked_file("gosystem"), None);
+        tmp_goup_home.create_dot_linked_file("gosystem", "/usr/lib/go", "go1.21.5")?;
+        assert!(tmp_goup_home.is_dot_linked_file_exists("gosystem"));
+        assert!(!tmp_goup_home.is_dot_unpacked_success_file_exists("gosystem"));
+        assert_eq!(
+            tmp_goup_home.read_dot_linked_file("gosystem"),
+            Some(PathBuf::from("/usr/lib/go"))
+        );
+        assert_eq!(
+            tmp_goup_home.read_dot_linked_version("gosystem").as_deref(),
+            Some("go1.21.5")
+        );
+
+        // linked before the version is recorded.
+        fs::create_dir_all(tmp_goup_home.version("goold"))?;
+        fs::write(tmp_goup_home.version_dot_linked("goold"), "/opt/go")?;
+        assert_eq!(
+            tmp_goup_home.read_dot_linked_file("goold"),
+            Some(PathBuf::from("/opt/go"))
+        );
+        assert_eq!(tmp_goup_home.read_dot_linked_version("goold"), None);
+        Ok(())
+    }
 }
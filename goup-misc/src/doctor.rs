@@ -0,0 +1,583 @@
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time;
+
+use indicatif::HumanBytes;
+use reqwest::blocking::Client;
+use which::which;
+
+use crate::Aliases;
+use crate::Dir;
+use crate::consts;
+use crate::op;
+
+/// an incomplete install or a leftover file modified within it may be the work of a
+/// running goup, `goup doctor --fix` leaves it alone.
+const IN_PROGRESS_GRACE: time::Duration = time::Duration::from_secs(10 * 60);
+
+/// status of a diagnosis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Status::Pass => "pass",
+            Status::Warn => "warn",
+            Status::Fail => "fail",
+        })
+    }
+}
+
+/// a safe repair of a problem, applied by `goup doctor --fix`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fix {
+    /// remove a dangling link, the link target is never touched.
+    RemoveLink(PathBuf),
+    /// remove a version directory left by an interrupted install.
+    RemoveDir(PathBuf),
+    /// remove a partially downloaded cache file.
+    RemoveFile(PathBuf),
+}
+
+impl Fix {
+    pub fn apply(&self) -> anyhow::Result<()> {
+        match self {
+            Fix::RemoveLink(p) => op::remove_symlink_dir(p)?,
+            Fix::RemoveDir(p) => fs::remove_dir_all(p)?,
+            Fix::RemoveFile(p) => fs::remove_file(p)?,
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Fix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fix::RemoveLink(p) => write!(f, "removed link {}", p.display()),
+            Fix::RemoveDir(p) => write!(f, "removed directory {}", p.display()),
+            Fix::RemoveFile(p) => write!(f, "removed file {}", p.display()),
+        }
+    }
+}
+
+/// a diagnosis of the goup environment.
+#[derive(Debug)]
+pub struct Diagnosis {
+    /// what is checked: GOUP_HOME, current, PATH ...
+    pub subject: String,
+    pub status: Status,
+    pub message: String,
+    /// how to resolve the problem by hand.
+    pub hint: Option<String>,
+    pub fixes: Vec<Fix>,
+}
+
+impl Diagnosis {
+    fn new(subject: &str, status: Status, message: String, hint: Option<String>) -> Self {
+        Self {
+            subject: subject.to_owned(),
+            status,
+            message,
+            hint,
+            fixes: Vec::new(),
+        }
+    }
+    fn pass(subject: &str, message: String) -> Self {
+        Self::new(subject, Status::Pass, message, None)
+    }
+    fn warn(subject: &str, message: String, hint: String) -> Self {
+        Self::new(subject, Status::Warn, message, Some(hint))
+    }
+    fn fail(subject: &str, message: String, hint: String) -> Self {
+        Self::new(subject, Status::Fail, message, Some(hint))
+    }
+    fn with_fixes(mut self, fixes: Vec<Fix>) -> Self {
+        self.fixes = fixes;
+        self
+    }
+}
+
+/// diagnose the goup environment: `GOUP_HOME`, the `current` link, PATH, GOROOT and
+/// GOTOOLCHAIN, installed versions, aliases, cache files and the connectivity to the hosts.
+pub fn diagnose() -> anyhow::Result<Vec<Diagnosis>> {
+    let goup_home = Dir::goup_home()?;
+    let now = time::SystemTime::now();
+    let mut diagnoses = vec![
+        check_goup_home(&goup_home),
+        check_current(&goup_home),
+        check_path(&goup_home),
+        check_goroot(&goup_home),
+        check_gotoolchain(),
+    ];
+    diagnoses.extend(check_versions(&goup_home, now)?);
+    diagnoses.extend(check_aliases(&goup_home)?);
+    diagnoses.extend(check_cache(&goup_home, now)?);
+    diagnoses.extend(check_hosts());
+    Ok(diagnoses)
+}
+
+fn check_goup_home(goup_home: &Dir) -> Diagnosis {
+    const SUBJECT: &str = "GOUP_HOME";
+    if !goup_home.exists() {
+        return Diagnosis::warn(
+            SUBJECT,
+            format!("{} does not exist", goup_home.display()),
+            "it is created by `goup install`".to_owned(),
+        );
+    }
+    if !goup_home.is_dir() {
+        return Diagnosis::fail(
+            SUBJECT,
+            format!("{} is not a directory", goup_home.display()),
+            format!(
+                "remove it, or set {} to another directory",
+                consts::GOUP_HOME
+            ),
+        );
+    }
+    let probe = goup_home.join(format!(".doctor-{}", std::process::id()));
+    match fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = fs::remove_file(&probe);
+            Diagnosis::pass(SUBJECT, format!("{} is writable", goup_home.display()))
+        }
+        Err(e) => Diagnosis::fail(
+            SUBJECT,
+            format!("{} is not writable: {e}", goup_home.display()),
+            "fix the owner or the permissions of it".to_owned(),
+        ),
+    }
+}
+
+fn check_current(goup_home: &Dir) -> Diagnosis {
+    const SUBJECT: &str = "current";
+    let current = goup_home.current();
+    match current.read_link() {
+        Ok(target) if target.exists() => Diagnosis::pass(
+            SUBJECT,
+            format!("{} -> {}", current.display(), target.display()),
+        ),
+        Ok(target) => Diagnosis::fail(
+            SUBJECT,
+            format!("{} -> {} is dangling", current.display(), target.display()),
+            "set another version with `goup set`".to_owned(),
+        )
+        .with_fixes(vec![Fix::RemoveLink(current.to_path_buf())]),
+        Err(_) if current.symlink_metadata().is_ok() => Diagnosis::fail(
+            SUBJECT,
+            format!("{} is not a link", current.display()),
+            "remove it, then set a version with `goup set`".to_owned(),
+        ),
+        Err(_) => Diagnosis::warn(
+            SUBJECT,
+            "no default Go is set".to_owned(),
+            "set one with `goup set`".to_owned(),
+        ),
+    }
+}
+
+/// positions of `current_bin` and the directory of the resolved `go` in PATH.
+fn path_order(
+    paths: &[PathBuf],
+    current_bin: &Path,
+    go_dir: Option<&Path>,
+) -> (Option<usize>, Option<usize>) {
+    let same = |a: &Path, b: &Path| {
+        a == b
+            || matches!(
+                (a.canonicalize(), b.canonicalize()),
+                (Ok(a), Ok(b)) if a == b
+            )
+    };
+    let current = paths.iter().position(|p| same(p, current_bin));
+    let go = go_dir.and_then(|go_dir| paths.iter().position(|p| same(p, go_dir)));
+    (current, go)
+}
+
+fn check_path(goup_home: &Dir) -> Diagnosis {
+    const SUBJECT: &str = "PATH";
+    let current_bin = goup_home.current_bin();
+    let paths: Vec<PathBuf> = env::var_os("PATH")
+        .map(|p| env::split_paths(&p).collect())
+        .unwrap_or_default();
+    let go_bin = which("go").ok();
+    let go_dir = go_bin.as_deref().and_then(Path::parent);
+    match path_order(&paths, &current_bin, go_dir) {
+        (None, _) => Diagnosis::fail(
+            SUBJECT,
+            format!("{} is not in PATH", current_bin.display()),
+            format!(
+                "add it to the front of PATH in your shell profile, such as `export PATH=\"{}:$PATH\"`",
+                current_bin.display()
+            ),
+        ),
+        (Some(_), None) => Diagnosis::warn(
+            SUBJECT,
+            "`go` is not found in PATH".to_owned(),
+            "install and set a version with `goup install` and `goup set`".to_owned(),
+        ),
+        (Some(current), Some(go)) if go < current => Diagnosis::fail(
+            SUBJECT,
+            format!(
+                "{} shadows goup's Go, {} comes before {} in PATH",
+                go_bin.unwrap_or_default().display(),
+                paths[go].display(),
+                current_bin.display()
+            ),
+            format!(
+                "move {} to the front of PATH, or uninstall the other Go",
+                current_bin.display()
+            ),
+        ),
+        (Some(_), Some(_)) => Diagnosis::pass(
+            SUBJECT,
+            format!("`go` resolves to {}", go_bin.unwrap_or_default().display()),
+        ),
+    }
+}
+
+fn check_goroot(goup_home: &Dir) -> Diagnosis {
+    const SUBJECT: &str = "GOROOT";
+    let Some(goroot) = env::var_os("GOROOT").filter(|s| !s.is_empty()) else {
+        return Diagnosis::pass(SUBJECT, "GOROOT is not set".to_owned());
+    };
+    let goroot = PathBuf::from(goroot);
+    match (goroot.canonicalize(), goup_home.current().canonicalize()) {
+        (Ok(a), Ok(b)) if a == b => {
+            Diagnosis::pass(SUBJECT, format!("GOROOT={} is goup's Go", goroot.display()))
+        }
+        _ => Diagnosis::fail(
+            SUBJECT,
+            format!(
+                "GOROOT={} overrides the GOROOT of goup's Go",
+                goroot.display()
+            ),
+            "unset GOROOT, the go command finds its GOROOT by itself".to_owned(),
+        ),
+    }
+}
+
+fn check_gotoolchain() -> Diagnosis {
+    const SUBJECT: &str = "GOTOOLCHAIN";
+    match env::var("GOTOOLCHAIN") {
+        Ok(v) if !v.is_empty() && !matches!(v.as_str(), "auto" | "local" | "path") => {
+            Diagnosis::warn(
+                SUBJECT,
+                format!("GOTOOLCHAIN={v} makes the go command switch to another toolchain"),
+                "unset GOTOOLCHAIN, or set it to 'local' to always use the Go set by goup"
+                    .to_owned(),
+            )
+        }
+        Ok(v) if !v.is_empty() => Diagnosis::pass(SUBJECT, format!("GOTOOLCHAIN={v}")),
+        _ => Diagnosis::pass(SUBJECT, "GOTOOLCHAIN is not set".to_owned()),
+    }
+}
+
+fn check_versions(goup_home: &Dir, now: time::SystemTime) -> anyhow::Result<Vec<Diagnosis>> {
+    const SUBJECT: &str = "versions";
+    if !goup_home.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut diagnoses = Vec::new();
+    let mut installed = 0;
+    let mut names: Vec<_> = goup_home
+        .read_dir()?
+        .filter_map(|v| v.ok())
+        .filter(|v| v.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|v| v.file_name().to_string_lossy().to_string())
+        .filter(|v| v.starts_with("go") && v != "gotip")
+        .collect();
+    names.sort();
+    for ver in names {
+        let name = ver.strip_prefix("go").unwrap_or(&ver);
+        if let Some(goroot) = goup_home.read_dot_linked_file(&ver) {
+            if goroot.is_dir() {
+                installed += 1;
+            } else {
+                diagnoses.push(Diagnosis::fail(
+                    SUBJECT,
+                    format!("linked {ver} -> {} is broken", goroot.display()),
+                    format!("unlink it with `goup remove {name}`"),
+                ));
+            }
+        } else if goup_home.is_dot_unpacked_success_file_exists(&ver) {
+            installed += 1;
+        } else if is_in_progress(goup_home.version(&ver), now) {
+            log::debug!("{ver} may be being installed, skipped");
+        } else {
+            let version_dir = goup_home.version(&ver);
+            diagnoses.push(
+                Diagnosis::warn(
+                    SUBJECT,
+                    format!("{} is an incomplete install", version_dir.display()),
+                    format!("install it again with `goup install ={name}`, or remove it"),
+                )
+                .with_fixes(vec![Fix::RemoveDir(version_dir.to_path_buf())]),
+            );
+        }
+    }
+    if diagnoses.is_empty() {
+        diagnoses.push(Diagnosis::pass(
+            SUBJECT,
+            format!("{installed} version(s) installed"),
+        ));
+    }
+    Ok(diagnoses)
+}
+
+fn check_aliases(goup_home: &Dir) -> anyhow::Result<Vec<Diagnosis>> {
+    const SUBJECT: &str = "aliases";
+    let aliases = Aliases::load(goup_home)?;
+    let diagnoses: Vec<_> = aliases
+        .iter()
+        .filter(|(_, version)| !goup_home.version_go(version).exists())
+        .map(|(name, version)| {
+            Diagnosis::warn(
+                SUBJECT,
+                format!("alias {name} targets {version}, which is not installed"),
+                format!("install it with `goup install {name}`"),
+            )
+        })
+        .collect();
+    if diagnoses.is_empty() && !aliases.is_empty() {
+        return Ok(vec![Diagnosis::pass(
+            SUBJECT,
+            "all aliases target installed versions".to_owned(),
+        )]);
+    }
+    Ok(diagnoses)
+}
+
+fn check_cache(goup_home: &Dir, now: time::SystemTime) -> anyhow::Result<Vec<Diagnosis>> {
+    const SUBJECT: &str = "cache";
+    let cache = goup_home.cache();
+    if !cache.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut files: Vec<_> = cache
+        .read_dir()?
+        .filter_map(|v| v.ok())
+        .filter(|v| v.file_type().is_ok_and(|t| t.is_file()))
+        .map(|v| v.file_name().to_string_lossy().to_string())
+        .collect();
+    files.sort();
+
+    let mut leftovers = Vec::new();
+    let mut size = 0;
+    let mut archives = 0;
+    for file in &files {
+        let partial = match file.strip_suffix(".sha256") {
+            Some(archive) => !files.iter().any(|f| f == archive),
+            None => !files.contains(&format!("{file}.sha256")),
+        };
+        if partial {
+            if is_in_progress(cache.join(file), now) {
+                log::debug!("{file} may be being downloaded, skipped");
+                continue;
+            }
+            leftovers.push(Fix::RemoveFile(cache.join(file)));
+        } else if !file.ends_with(".sha256") {
+            archives += 1;
+            size += fs::metadata(cache.join(file)).map_or(0, |m| m.len());
+        }
+    }
+    if leftovers.is_empty() {
+        return Ok(vec![Diagnosis::pass(
+            SUBJECT,
+            format!(
+                "{archives} archive(s), {}, free them with `goup clean`",
+                HumanBytes(size)
+            ),
+        )]);
+    }
+    Ok(vec![
+        Diagnosis::warn(
+            SUBJECT,
+            format!(
+                "{} leftover file(s) of interrupted downloads in {}",
+                leftovers.len(),
+                cache.display()
+            ),
+            "remove them with `goup clean`".to_owned(),
+        )
+        .with_fixes(leftovers),
+    ])
+}
+
+/// whether the file, or any entry of the directory, is modified within `IN_PROGRESS_GRACE`.
+fn is_in_progress<P: AsRef<Path>>(path: P, now: time::SystemTime) -> bool {
+    let path = path.as_ref();
+    let Ok(meta) = fs::symlink_metadata(path) else {
+        return false;
+    };
+    let recent = meta.modified().is_ok_and(|t| {
+        now.duration_since(t)
+            .map_or(true, |d| d < IN_PROGRESS_GRACE)
+    });
+    if recent || !meta.is_dir() {
+        return recent;
+    }
+    fs::read_dir(path).is_ok_and(|dir| {
+        dir.filter_map(|v| v.ok())
+            .any(|v| is_in_progress(v.path(), now))
+    })
+}
+
+fn check_hosts() -> Vec<Diagnosis> {
+    let Ok(client) = Client::builder()
+        .timeout(time::Duration::from_secs(5))
+        .build()
+    else {
+        return Vec::new();
+    };
+    [
+        (
+            consts::GOUP_GO_HOST,
+            format!("{}/VERSION?m=text", consts::go_host()),
+        ),
+        (
+            consts::GOUP_GO_DOWNLOAD_BASE_URL,
+            consts::go_download_base_url(),
+        ),
+    ]
+    .into_iter()
+    .map(|(subject, url)| match client.head(&url).send() {
+        Ok(resp) => Diagnosis::pass(subject, format!("{url} is reachable ({})", resp.status())),
+        Err(e) => Diagnosis::warn(
+            subject,
+            format!("{url} is unreachable: {e}"),
+            format!("check the network and proxy settings, or set {subject} to a reachable mirror"),
+        ),
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_order() {
+        let current_bin = Path::new("/home/dev/.goup/current/bin");
+        let paths = vec![
+            PathBuf::from("/usr/local/go/bin"),
+            PathBuf::from("/home/dev/.goup/current/bin"),
+            PathBuf::from("/usr/bin"),
+        ];
+        assert_eq!(path_order(&paths, current_bin, None), (Some(1), None));
+        assert_eq!(
+            path_order(&paths, current_bin, Some(current_bin)),
+            (Some(1), Some(1))
+        );
+        assert_eq!(
+            path_order(&paths, current_bin, Some(Path::new("/usr/local/go/bin"))),
+            (Some(1), Some(0))
+        );
+        assert_eq!(
+            path_order(&paths[2..], current_bin, Some(Path::new("/usr/bin"))),
+            (None, Some(0))
+        );
+    }
+
+    #[test]
+    fn test_check_versions() -> anyhow::Result<()> {
+        let tmp_home_dir = tempfile::tempdir()?;
+        let goup_home = Dir::new(tmp_home_dir);
+        fs::create_dir_all(goup_home.version_go("go1.21.2"))?;
+        goup_home.create_dot_unpacked_success_file("go1.21.2")?;
+        let now = time::SystemTime::now();
+        let diagnoses = check_versions(&goup_home, now)?;
+        assert_eq!(diagnoses.len(), 1);
+        assert_eq!(diagnoses[0].status, Status::Pass);
+
+        // being installed.
+        fs::create_dir_all(goup_home.version_go("go1.22.0"))?;
+        let diagnoses = check_versions(&goup_home, now)?;
+        assert_eq!(diagnoses.len(), 1);
+        assert_eq!(diagnoses[0].status, Status::Pass);
+
+        let later = now + IN_PROGRESS_GRACE * 2;
+        let diagnoses = check_versions(&goup_home, later)?;
+        assert_eq!(diagnoses.len(), 1);
+        assert_eq!(diagnoses[0].status, Status::Warn);
+        assert_eq!(
+            diagnoses[0].fixes,
+            vec![Fix::RemoveDir(goup_home.version("go1.22.0").to_path_buf())]
+        );
+        diagnoses[0].fixes[0].apply()?;
+        assert!(!goup_home.version("go1.22.0").exists());
+        assert!(goup_home.version("go1.21.2").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_cache() -> anyhow::Result<()> {
+        let tmp_home_dir = tempfile::tempdir()?;
+        let goup_home = Dir::new(tmp_home_dir);
+        fs::create_dir_all(goup_home.cache())?;
+        fs::write(goup_home.cache_file("go1.21.2.linux-amd64.tar.gz"), "a")?;
+        fs::write(
+            goup_home.cache_file("go1.21.2.linux-amd64.tar.gz.sha256"),
+            "b",
+        )?;
+        let now = time::SystemTime::now();
+        let diagnoses = check_cache(&goup_home, now)?;
+        assert_eq!(diagnoses[0].status, Status::Pass);
+
+        fs::write(goup_home.cache_file("go1.22.0.linux-amd64.tar.gz"), "a")?;
+        fs::write(
+            goup_home.cache_file("go1.20.linux-amd64.tar.gz.sha256"),
+            "b",
+        )?;
+        // being downloaded.
+        let diagnoses = check_cache(&goup_home, now)?;
+        assert_eq!(diagnoses[0].status, Status::Pass);
+
+        let later = now + IN_PROGRESS_GRACE * 2;
+        let diagnoses = check_cache(&goup_home, later)?;
+        assert_eq!(diagnoses[0].status, Status::Warn);
+        assert_eq!(
+            diagnoses[0].fixes,
+            vec![
+                Fix::RemoveFile(
+                    goup_home
+                        .cache_file("go1.20.linux-amd64.tar.gz.sha256")
+                        .to_path_buf()
+                ),
+                Fix::RemoveFile(
+                    goup_home
+                        .cache_file("go1.22.0.linux-amd64.tar.gz")
+                        .to_path_buf()
+                ),
+            ]
+        );
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_current() -> anyhow::Result<()> {
+        let tmp_home_dir = tempfile::tempdir()?;
+        let goup_home = Dir::new(tmp_home_dir);
+        fs::create_dir_all(&*goup_home)?;
+        assert_eq!(check_current(&goup_home).status, Status::Warn);
+
+        std::os::unix::fs::symlink(goup_home.version_go("go1.21.2"), goup_home.current())?;
+        let diagnosis = check_current(&goup_home);
+        assert_eq!(diagnosis.status, Status::Fail);
+        diagnosis.fixes[0].apply()?;
+        assert!(goup_home.current().symlink_metadata().is_err());
+
+        fs::create_dir_all(goup_home.version_go("go1.21.2"))?;
+        std::os::unix::fs::symlink(goup_home.version_go("go1.21.2"), goup_home.current())?;
+        assert_eq!(check_current(&goup_home).status, Status::Pass);
+        Ok(())
+    }
+}
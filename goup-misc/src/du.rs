@@ -0,0 +1,163 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cache::SharedCache;
+use crate::{Dir, op};
+
+/// what a [`Usage`] measures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsageKind {
+    /// `${GOUP_HOME}/{version}` of an installed version.
+    Version,
+    /// `${GOUP_HOME}/{version}` of a linked toolchain, the linked GOROOT is not counted.
+    Linked,
+    /// `${GOUP_HOME}/cache`
+    Cache,
+    /// `GOUP_CACHE_DIR`
+    SharedCache,
+}
+
+impl UsageKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UsageKind::Version => "version",
+            UsageKind::Linked => "linked",
+            UsageKind::Cache => "cache",
+            UsageKind::SharedCache => "shared-cache",
+        }
+    }
+}
+
+/// disk usage of a version directory or a cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Usage {
+    /// version directory name, such as `go1.21.5`, or `cache`.
+    pub name: String,
+    pub kind: UsageKind,
+    pub path: PathBuf,
+    /// total size in bytes of the files.
+    pub size: u64,
+    /// bytes of the files which are hard linked with an entry measured before, such as by
+    /// `goup dedupe` or the shared cache.
+    pub shared: u64,
+}
+
+/// disk usage of goup.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiskUsage {
+    pub items: Vec<Usage>,
+    /// sum of the sizes.
+    pub total: u64,
+    /// the bytes actually used, hard linked files are counted once.
+    pub disk: u64,
+}
+
+/// measure the installed versions, the cache and the shared cache.
+pub fn disk_usage() -> anyhow::Result<DiskUsage> {
+    let goup_home = Dir::goup_home()?;
+    let mut paths = Vec::new();
+    for v in op::list_go_version()? {
+        let Some(dir) = v.path.parent() else {
+            continue;
+        };
+        let name = dir
+            .file_name()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let kind = match v.linked {
+            Some(_) => UsageKind::Linked,
+            None => UsageKind::Version,
+        };
+        paths.push((name, kind, dir.to_path_buf()));
+    }
+    if goup_home.cache().exists() {
+        paths.push((
+            "cache".to_owned(),
+            UsageKind::Cache,
+            goup_home.cache().to_path_buf(),
+        ));
+    }
+    if let Some(shared_cache) = SharedCache::from_env()
+        && shared_cache.root().exists()
+    {
+        paths.push((
+            "shared-cache".to_owned(),
+            UsageKind::SharedCache,
+            shared_cache.root().to_path_buf(),
+        ));
+    }
+
+    let mut seen = HashSet::new();
+    let mut du = DiskUsage::default();
+    for (name, kind, path) in paths {
+        let (size, unique) = measure(&path, &mut seen)?;
+        du.total += size;
+        du.disk += unique;
+        du.items.push(Usage {
+            name,
+            kind,
+            path,
+            size,
+            shared: size - unique,
+        });
+    }
+    Ok(du)
+}
+
+/// the total size of the files under the path, symlinks are not followed.
+pub fn dir_size<P: AsRef<Path>>(path: P) -> anyhow::Result<u64> {
+    Ok(measure(path.as_ref(), &mut HashSet::new())?.0)
+}
+
+/// the total size and the size of the files not seen yet, symlinks are not followed.
+fn measure(path: &Path, seen: &mut HashSet<(u64, u64)>) -> anyhow::Result<(u64, u64)> {
+    let metadata = fs::symlink_metadata(path)?;
+    if !metadata.is_dir() {
+        let size = metadata.len();
+        return Ok((size, if inode_seen(&metadata, seen) { 0 } else { size }));
+    }
+    let (mut size, mut unique) = (0, 0);
+    for entry in fs::read_dir(path)? {
+        let (s, u) = measure(&entry?.path(), seen)?;
+        size += s;
+        unique += u;
+    }
+    Ok((size, unique))
+}
+
+#[cfg(unix)]
+fn inode_seen(metadata: &fs::Metadata, seen: &mut HashSet<(u64, u64)>) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    metadata.nlink() > 1 && !seen.insert((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn inode_seen(_metadata: &fs::Metadata, _seen: &mut HashSet<(u64, u64)>) -> bool {
+    false
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure() -> anyhow::Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        let a = tmp_dir.path().join("a");
+        let b = tmp_dir.path().join("b");
+        fs::create_dir_all(&a)?;
+        fs::create_dir_all(&b)?;
+        fs::write(a.join("x"), "12345")?;
+        fs::write(a.join("y"), "123")?;
+        fs::hard_link(a.join("x"), b.join("x"))?;
+        fs::write(b.join("z"), "12")?;
+
+        let mut seen = HashSet::new();
+        assert_eq!(measure(&a, &mut seen)?, (8, 8));
+        assert_eq!(measure(&b, &mut seen)?, (7, 2));
+        // measured again, only the hard linked files are seen
+        assert_eq!(measure(&a, &mut seen)?, (8, 3));
+        Ok(())
+    }
+}
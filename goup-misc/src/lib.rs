@@ -1,10 +1,22 @@
+mod alias;
 mod dir;
+mod req;
 mod toolchain;
 mod version;
 
+pub mod cache;
 pub mod consts;
+pub mod dedupe;
+pub mod doctor;
+pub mod du;
+pub mod manifest;
 pub mod op;
+pub mod progress;
+pub mod prune;
+pub mod sync;
 
+pub use alias::Aliases;
 pub use dir::Dir;
+pub use req::GoVersionReq;
 pub use toolchain::{Toolchain, ToolchainFilter};
-pub use version::Version;
+pub use version::{GoVersion, Linked, Prerelease, Version};
@@ -0,0 +1,202 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::anyhow;
+use sha2::{Digest, Sha256};
+
+/// a file recorded in the manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// the manifest of the extracted files of a Go version, keyed by the path relative to GOROOT.
+/// it is stored as `{sha256} {size} {path}` lines in `${GOUP_HOME}/{version}/.manifest`.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Manifest(BTreeMap<String, Entry>);
+
+/// the difference between the recorded manifest and the files on disk.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    /// files whose size or sha256 changed.
+    pub modified: Vec<String>,
+    /// recorded files which no longer exist.
+    pub missing: Vec<String>,
+    /// files which are not recorded.
+    pub extra: Vec<String>,
+}
+
+impl Report {
+    /// no file is modified or missing, extra files are allowed.
+    pub fn is_intact(&self) -> bool {
+        self.modified.is_empty() && self.missing.is_empty()
+    }
+}
+
+impl Manifest {
+    /// record all regular files under root, symlinks are not followed.
+    pub fn build<P: AsRef<Path>>(root: P) -> anyhow::Result<Self> {
+        let mut manifest = Self::default();
+        manifest.walk(root.as_ref(), "")?;
+        Ok(manifest)
+    }
+
+    fn walk(&mut self, dir: &Path, prefix: &str) -> anyhow::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            let rel = if prefix.is_empty() {
+                name
+            } else {
+                format!("{prefix}/{name}")
+            };
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                self.walk(&entry.path(), &rel)?;
+            } else if file_type.is_file() {
+                let path = entry.path();
+                let size = entry.metadata()?.len();
+                let sha256 = compute_file_sha256(&path)?;
+                self.0.insert(rel, Entry { size, sha256 });
+            }
+        }
+        Ok(())
+    }
+
+    /// load the manifest file.
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let mut manifest = Self::default();
+        for line in content.lines().filter(|l| !l.is_empty()) {
+            let mut fields = line.splitn(3, ' ');
+            let (Some(sha256), Some(size), Some(path)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                return Err(anyhow!("invalid manifest line: {line}"));
+            };
+            manifest.0.insert(
+                path.to_owned(),
+                Entry {
+                    size: size.parse()?,
+                    sha256: sha256.to_owned(),
+                },
+            );
+        }
+        Ok(manifest)
+    }
+
+    /// save the manifest file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        let content: String = self
+            .0
+            .iter()
+            .map(|(path, e)| format!("{} {} {path}\n", e.sha256, e.size))
+            .collect();
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// compare the recorded manifest with the actual one.
+    pub fn compare(&self, actual: &Manifest) -> Report {
+        let mut report = Report::default();
+        for (path, entry) in &self.0 {
+            match actual.0.get(path) {
+                Some(e) if e == entry => {}
+                Some(_) => report.modified.push(path.to_owned()),
+                None => report.missing.push(path.to_owned()),
+            }
+        }
+        report.extra = actual
+            .0
+            .keys()
+            .filter(|p| !self.0.contains_key(*p))
+            .cloned()
+            .collect();
+        report
+    }
+
+    /// iterate the recorded files by path.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Entry)> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// compute the hex encoded sha256 of the file.
+pub fn compute_file_sha256<P: AsRef<Path>>(path: P) -> anyhow::Result<String> {
+    let mut context = Sha256::new();
+    let mut file = File::open(path)?;
+    let mut buffer = [0; 64 * 1024];
+    loop {
+        let bytes_read = file.read(&mut buffer)?;
+        if bytes_read == 0 {
+            break;
+        }
+        context.update(&buffer[..bytes_read]);
+    }
+    Ok(format!("{:x}", context.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_file_sha256() -> anyhow::Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        let file = tmp_dir.path().join("file");
+        fs::write(&file, "abc")?;
+        assert_eq!(
+            compute_file_sha256(&file)?,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_manifest() -> anyhow::Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        let root = tmp_dir.path().join("go");
+        fs::create_dir_all(root.join("bin"))?;
+        fs::create_dir_all(root.join("src").join("fmt"))?;
+        fs::write(root.join("VERSION"), "go1.21.5")?;
+        fs::write(root.join("bin").join("go"), "go")?;
+        fs::write(root.join("src").join("fmt").join("print file.go"), "fmt")?;
+
+        let manifest = Manifest::build(&root)?;
+        assert_eq!(manifest.len(), 3);
+        let manifest_file = tmp_dir.path().join(".manifest");
+        manifest.save(&manifest_file)?;
+        let recorded = Manifest::load(&manifest_file)?;
+        assert_eq!(recorded, manifest);
+        assert_eq!(
+            recorded.compare(&Manifest::build(&root)?),
+            Report::default()
+        );
+
+        fs::write(root.join("bin").join("go"), "evil")?;
+        fs::remove_file(root.join("VERSION"))?;
+        fs::write(root.join("bin").join("gofmt"), "gofmt")?;
+        let report = recorded.compare(&Manifest::build(&root)?);
+        assert!(!report.is_intact());
+        assert_eq!(
+            report,
+            Report {
+                modified: vec!["bin/go".to_owned()],
+                missing: vec!["VERSION".to_owned()],
+                extra: vec!["bin/gofmt".to_owned()],
+            }
+        );
+        Ok(())
+    }
+}
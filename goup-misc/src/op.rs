@@ -1,22 +1,31 @@
+use std::env;
 use std::fs;
 use std::fs::DirEntry;
 use std::ops::Deref;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
 use std::time;
 
 use anyhow::Result;
 use anyhow::anyhow;
-use indicatif::ProgressBar;
 use regex::Regex;
 use reqwest::blocking::Client;
-use semver::Op;
-use semver::VersionReq;
 use serde::{Deserialize, Serialize};
 
+use crate::Aliases;
 use crate::Dir;
+use crate::GoVersion;
+use crate::GoVersionReq;
+use crate::Linked;
 use crate::ToolchainFilter;
 use crate::Version;
+use crate::cache;
+use crate::consts;
+use crate::manifest::{Manifest, Report};
+use crate::progress;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GoFile {
     pub arch: String,
     pub filename: String,
@@ -27,18 +36,41 @@ pub struct GoFile {
     pub version: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub(crate) struct GoRelease {
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GoRelease {
     pub version: String,
     pub stable: bool,
-    // pub files: Vec<GoFile>,
+    #[serde(default)]
+    pub files: Vec<GoFile>,
+}
+
+impl GoRelease {
+    /// platforms which have a binary archive of this release, such as `linux/amd64`.
+    pub fn platforms(&self) -> Vec<String> {
+        self.files
+            .iter()
+            .filter(|f| f.kind == "archive")
+            .map(|f| format!("{}/{}", f.os, f.arch))
+            .collect()
+    }
 }
 
 pub fn list_upstream_go_versions_filter(
     host: &str,
     filter: Option<ToolchainFilter>,
 ) -> anyhow::Result<Vec<String>> {
-    let ver = list_upstream_go_versions(host)?;
+    Ok(list_upstream_go_releases_filter(host, filter)?
+        .into_iter()
+        .map(|v| v.version)
+        .collect())
+}
+
+/// list upstream go releases which match the filter, the version is without `go` prefix.
+pub fn list_upstream_go_releases_filter(
+    host: &str,
+    filter: Option<ToolchainFilter>,
+) -> anyhow::Result<Vec<GoRelease>> {
+    let releases = list_upstream_go_releases(host)?;
     let re = filter.map_or_else(
         || "(.+)".to_owned(),
         |f| match f {
@@ -57,53 +89,212 @@ pub fn list_upstream_go_versions_filter(
         },
     );
     let re = Regex::new(&re)?;
-    Ok(ver
+    Ok(releases
         .into_iter()
-        .filter_map(|v| re.is_match(&v).then_some(v))
+        .filter(|v| re.is_match(&v.version))
         .collect())
 }
 
 /// list upstream go versions from http.
 pub fn list_upstream_go_versions(host: &str) -> anyhow::Result<Vec<String>> {
-    let spinner = ProgressBar::new_spinner();
-    spinner.enable_steady_tick(time::Duration::from_millis(100));
+    Ok(list_upstream_go_releases(host)?
+        .into_iter()
+        .map(|v| v.version)
+        .collect())
+}
+
+/// list upstream go releases from http, oldest first, the version is without `go` prefix.
+/// the fetched release index is cached in `${HOME}/.goup/releases.json`.
+pub fn list_upstream_go_releases(host: &str) -> anyhow::Result<Vec<GoRelease>> {
+    let spinner = progress::spinner();
 
     spinner.set_message("Fetching upstream Go versions");
-    let v = Client::builder()
+    log::debug!("fetching {host}/dl/?mode=json&include=all");
+    let releases = Client::builder()
         .timeout(time::Duration::from_secs(10))
         .build()?
         .get(format!("{host}/dl/?mode=json&include=all"))
         .send()?
-        .json::<Vec<GoRelease>>()?
+        .json::<Vec<GoRelease>>()?;
+    spinner.finish_and_clear();
+    if let Err(e) = save_release_index(&releases) {
+        log::warn!("Saving the release index failed: {e}");
+    }
+    Ok(oldest_first(releases))
+}
+
+/// list go releases from the release index cached by the last upstream fetch, never fetches
+/// from http. oldest first, the version is without `go` prefix, empty if nothing cached.
+pub fn list_cached_go_releases() -> anyhow::Result<Vec<GoRelease>> {
+    let release_index = Dir::goup_home()?.release_index();
+    if !release_index.exists() {
+        return Ok(Vec::new());
+    }
+    let releases = serde_json::from_str(&fs::read_to_string(&release_index)?)?;
+    Ok(oldest_first(releases))
+}
+
+/// save the upstream release index(newest first, with `go` prefix) atomically.
+fn save_release_index(releases: &[GoRelease]) -> anyhow::Result<()> {
+    let goup_home = Dir::goup_home()?;
+    if !goup_home.exists() {
+        fs::create_dir_all(&goup_home)?;
+    }
+    let release_index = goup_home.release_index();
+    let tmp = release_index.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_string(releases)?)?;
+    fs::rename(&tmp, &release_index)?;
+    Ok(())
+}
+
+/// merge the releases(newest first, with `go` prefix) into the cached release index,
+/// such as the index of an imported bundle.
+pub fn merge_release_index(releases: Vec<GoRelease>) -> anyhow::Result<()> {
+    let release_index = Dir::goup_home()?.release_index();
+    let mut merged: Vec<GoRelease> = if release_index.exists() {
+        serde_json::from_str(&fs::read_to_string(&release_index)?)?
+    } else {
+        Vec::new()
+    };
+    merge_releases(&mut merged, releases);
+    save_release_index(&merged)
+}
+
+/// merge the releases into the release index, the files of the same release are merged.
+/// the release index is sorted newest first.
+pub fn merge_releases(index: &mut Vec<GoRelease>, releases: Vec<GoRelease>) {
+    for release in releases {
+        match index.iter_mut().find(|r| r.version == release.version) {
+            Some(r) => {
+                for file in release.files {
+                    if !r.files.iter().any(|f| f.filename == file.filename) {
+                        r.files.push(file);
+                    }
+                }
+            }
+            None => index.push(release),
+        }
+    }
+    index.sort_by_cached_key(|r| std::cmp::Reverse(r.version.parse::<GoVersion>().ok()));
+}
+
+/// upstream releases are newest first with `go` prefix, trim the prefix and reverse.
+fn oldest_first(releases: Vec<GoRelease>) -> Vec<GoRelease> {
+    releases
         .into_iter()
-        .map(|v| v.version.trim_start_matches("go").to_string())
+        .map(|mut v| {
+            v.version = v.version.trim_start_matches("go").to_string();
+            v
+        })
         .rev()
+        .collect()
+}
+
+/// check the go version is released upstream, and is built for this platform whose archive
+/// filename is given, before downloading. suggests the nearest versions if it does not exist.
+pub fn check_go_release(
+    version: &str,
+    archive: &str,
+    releases: &[GoRelease],
+) -> anyhow::Result<()> {
+    let ver = version.trim_start_matches("go");
+    let Some(release) = releases.iter().find(|r| r.version == ver) else {
+        let suggestions = suggest_go_versions(ver, releases);
+        if suggestions.is_empty() {
+            anyhow::bail!("Go version {ver} does not exist.");
+        }
+        anyhow::bail!(
+            "Go version {ver} does not exist, did you mean {}?",
+            suggestions.join(", ")
+        );
+    };
+    // the release index may have no files, such as a mirror.
+    if release.files.is_empty() || release.files.iter().any(|f| f.filename == archive) {
+        return Ok(());
+    }
+    anyhow::bail!(
+        "Go version {ver} is not built for this platform, {archive} does not exist. \
+        It is built for {}.",
+        release.platforms().join(", ")
+    )
+}
+
+/// the versions nearest to the misspelled one by edit distance, newest first.
+fn suggest_go_versions(ver: &str, releases: &[GoRelease]) -> Vec<String> {
+    const MAX_DISTANCE: usize = 2;
+    const MAX_SUGGESTIONS: usize = 3;
+    let mut suggestions: Vec<_> = releases
+        .iter()
+        .rev()
+        .map(|r| (edit_distance(ver, &r.version), &r.version))
+        .filter(|(d, _)| *d <= MAX_DISTANCE)
         .collect();
-    spinner.finish_and_clear();
-    Ok(v)
+    // stable sort, so the newest comes first within the same distance.
+    suggestions.sort_by_key(|(d, _)| *d);
+    suggestions
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, v)| v.to_owned())
+        .collect()
 }
 
+/// levenshtein distance of the two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cur = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                prev
+            } else {
+                1 + prev.min(row[j]).min(cur)
+            };
+            prev = cur;
+        }
+    }
+    row[b.len()]
+}
+
+/// the newest upstream go version which satisfies the requirement, see [`GoVersionReq`].
+/// an exact version is returned without fetching upstream.
 pub fn match_version_req(host: &str, ver_pattern: &str) -> anyhow::Result<String> {
+    match_version_req_with(ver_pattern, || list_upstream_go_versions(host))
+}
+
+/// the newest go version of `versions` which satisfies the requirement, `versions` is only
+/// called if the requirement is not an exact version.
+pub fn match_version_req_with<F>(ver_pattern: &str, versions: F) -> anyhow::Result<String>
+where
+    F: FnOnce() -> anyhow::Result<Vec<String>>,
+{
     log::debug!("version request pattern: {ver_pattern}");
-    let ver_req = VersionReq::parse(ver_pattern)?;
-    // 是否是精确匹配, 如果是则直接返回
-    if ver_req.comparators.iter().all(|v| v.op == Op::Exact) {
-        return Ok(ver_pattern.trim_start_matches('=').to_owned());
-    }
-    for ver in list_upstream_go_versions(host)?.iter().rev() {
-        if ver_req.matches(&Version::semantic(ver)?) {
-            return Ok(ver.to_owned());
+    let ver_req = match ver_pattern.parse::<GoVersionReq>() {
+        Ok(ver_req) => ver_req,
+        // not a requirement, such as a typo, it is validated as an exact version.
+        Err(e) => {
+            log::debug!("{e}");
+            return Ok(ver_pattern.trim_start_matches('=').to_owned());
         }
+    };
+    if let GoVersionReq::Exact(ver) = ver_req {
+        return Ok(ver.to_string());
     }
-    Err(anyhow!("not any match version!"))
+    let versions: Vec<GoVersion> = versions()?.iter().filter_map(|v| v.parse().ok()).collect();
+    ver_req
+        .select(&versions)
+        .map(ToString::to_string)
+        .ok_or_else(|| anyhow!("No Go version matches '{ver_pattern}'."))
 }
 
 /// get upstream latest go version.
 pub fn get_upstream_latest_go_version(host: &str) -> anyhow::Result<String> {
-    let spinner = ProgressBar::new_spinner();
-    spinner.enable_steady_tick(time::Duration::from_millis(100));
+    let spinner = progress::spinner();
 
     spinner.set_message("Fetching upstream latest Go version");
+    log::debug!("fetching {host}/VERSION?m=text");
     let body = Client::builder()
         .timeout(time::Duration::from_secs(10))
         .build()?
@@ -130,6 +321,7 @@ pub fn list_go_version() -> anyhow::Result<Vec<Version>> {
     // may be current not exist
     let current = goup_home.current().read_link();
     let current = current.as_ref();
+    let aliases = Aliases::load(&goup_home)?;
     let dir: Result<Vec<DirEntry>, _> = goup_home.read_dir()?.collect();
     let mut version_dirs: Vec<_> = dir?
         .iter()
@@ -139,12 +331,39 @@ pub fn list_go_version() -> anyhow::Result<Vec<Version>> {
             }
 
             let ver = v.file_name().to_string_lossy().to_string();
+            let active = current.is_ok_and(|vv| vv == goup_home.version_go(&ver).deref());
+            if goup_home.is_dot_linked_file_exists(&ver) {
+                let goroot = goup_home.read_dot_linked_file(&ver)?;
+                // a GOROOT without VERSION file, such as a custom build, has the version
+                // recorded at link time.
+                let go_version = read_goroot_version_file(&goroot)
+                    .ok()
+                    .or_else(|| goup_home.read_dot_linked_version(&ver))
+                    .and_then(|v| go_release_of(&v));
+                let Some(go_version) = go_version else {
+                    log::warn!("linked toolchain {ver} is broken: the Go version is unknown");
+                    return None;
+                };
+                return Some(Version {
+                    version: go_version,
+                    active,
+                    path: goup_home.version_go(&ver).to_path_buf(),
+                    linked: Some(Linked {
+                        name: ver.strip_prefix("go").unwrap_or(&ver).to_owned(),
+                        goroot,
+                    }),
+                    aliases: aliases.names_of(&ver),
+                });
+            }
             if ver != "gotip" && !goup_home.is_dot_unpacked_success_file_exists(&ver) {
                 return None;
             }
             Some(Version {
-                version: Version::semantic(ver.trim_start_matches("go")).ok()?,
-                active: current.is_ok_and(|vv| vv == goup_home.version_go(ver).deref()),
+                version: ver.parse().ok()?,
+                active,
+                path: goup_home.version_go(&ver).to_path_buf(),
+                linked: None,
+                aliases: aliases.names_of(&ver),
             })
         })
         .collect();
@@ -152,52 +371,238 @@ pub fn list_go_version() -> anyhow::Result<Vec<Version>> {
     Ok(version_dirs)
 }
 
-/// set active go version
+/// list the names of go versions installed by goup, linked toolchains are excluded.
+pub fn list_managed_go_versions() -> anyhow::Result<Vec<String>> {
+    Ok(list_go_version()?
+        .into_iter()
+        .filter(|v| v.linked.is_none())
+        .filter_map(|v| v.dir_name())
+        .collect())
+}
+
+/// resolve the version to the installed go versions, newest first, as directory names.
+/// an alias or a linked toolchain name resolves to itself, a requirement is matched against
+/// the installed versions, see [`GoVersionReq`], so `1.20` is the newest installed 1.20.x
+/// even if `go1.20` is installed. any other name resolves to its installed directory.
+pub fn match_installed_go_versions(ver_pattern: &str) -> anyhow::Result<Vec<String>> {
+    let goup_home = Dir::goup_home()?;
+    let version = Version::resolve(ver_pattern)?;
+    let installed_dir = goup_home.version_go(&version).exists();
+    let named = Aliases::load(&goup_home)?.get(ver_pattern).is_some()
+        || goup_home.is_dot_linked_file_exists(&version);
+    let ver_req = match ver_pattern.parse::<GoVersionReq>() {
+        Ok(ver_req) if !named => ver_req,
+        _ => {
+            return Ok(if installed_dir {
+                vec![version]
+            } else {
+                Vec::new()
+            });
+        }
+    };
+    let installed = list_go_version()?;
+    let matched = ver_req.filter(installed.iter().map(|v| &v.version));
+    Ok(installed
+        .iter()
+        .rev()
+        .filter(|v| matched.contains(&&v.version))
+        .filter_map(Version::dir_name)
+        .collect())
+}
+
+/// the newest installed go version which satisfies the version, see
+/// [`match_installed_go_versions`]. the normalized version if nothing installed matches.
+pub fn resolve_installed_go_version(ver_pattern: &str) -> anyhow::Result<String> {
+    match match_installed_go_versions(ver_pattern)?.into_iter().next() {
+        Some(version) => Ok(version),
+        None => Version::resolve(ver_pattern),
+    }
+}
+
+/// set active go version, the version may be an alias or a requirement, such as `1.21`.
 pub fn set_go_version(version: &str) -> anyhow::Result<()> {
-    let version = Version::normalize(version);
+    let ver_pattern = version;
+    let version = resolve_installed_go_version(ver_pattern)?;
     let goup_home = Dir::goup_home()?;
     let original = goup_home.version_go(&version);
     if !original.exists() {
-        anyhow::bail!("Go version {version} is not installed. Install it with `goup install`.");
+        if matches!(ver_pattern.parse(), Ok(GoVersionReq::Exact(_)) | Err(_)) {
+            anyhow::bail!("Go version {version} is not installed. Install it with `goup install`.");
+        }
+        anyhow::bail!(
+            "No installed Go version matches '{ver_pattern}'. Install one with `goup set --install`."
+        );
     }
 
     let link = goup_home.current();
     let _ = fs::remove_dir_all(&link);
+    symlink_dir(original, &link)?;
+
+    println!("Default Go is set to '{version}'");
+    Ok(())
+}
+
+/// link an externally installed GOROOT into `GOUP_HOME` as `go{name}`,
+/// returns the Go version of the GOROOT.
+pub fn link_go_version(name: &str, goroot: &Path) -> anyhow::Result<String> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+    {
+        anyhow::bail!("Invalid link name '{name}'.");
+    }
+    let version = Version::normalize(name);
+    if version == "gotip" {
+        anyhow::bail!("'{name}' is reserved.");
+    }
+    let goroot = std::path::absolute(goroot)?;
+    let go_version = goroot_version(&goroot)?;
+
+    let goup_home = Dir::goup_home()?;
+    if Aliases::load(&goup_home)?.get(name).is_some() {
+        anyhow::bail!("'{name}' is an alias, remove it with `goup alias --delete {name}` first.");
+    }
+    let version_dir = goup_home.version(&version);
+    if version_dir.exists() {
+        anyhow::bail!("Go version {version} already exists, remove it with `goup remove` first.");
+    }
+    fs::create_dir_all(&version_dir)?;
+    symlink_dir(&goroot, goup_home.version_go(&version))?;
+    goup_home.create_dot_linked_file(&version, &goroot, &go_version)?;
+    Ok(go_version)
+}
+
+/// get the Go version of a GOROOT, such as `go1.21.5` or `devel go1.23-e8ee1dc`.
+/// it reads `${GOROOT}/VERSION` and checks it against `${GOROOT}/bin/go version`.
+pub fn goroot_version(goroot: &Path) -> anyhow::Result<String> {
+    if !goroot.is_dir() {
+        anyhow::bail!("{} is not a directory.", goroot.display());
+    }
+    let go_bin = goroot
+        .join("bin")
+        .join(format!("go{}", env::consts::EXE_SUFFIX));
+    if !go_bin.is_file() {
+        anyhow::bail!(
+            "{} is not a GOROOT, missing {}.",
+            goroot.display(),
+            go_bin.display()
+        );
+    }
+    let output = Command::new(&go_bin)
+        .arg("version")
+        // never let the go command switch to another toolchain.
+        .env("GOTOOLCHAIN", "local")
+        .env_remove("GOROOT")
+        .output()?;
+    if !output.status.success() {
+        anyhow::bail!("Running `{} version` failed.", go_bin.display());
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let go_version = parse_go_version_output(&stdout)
+        .ok_or_else(|| anyhow!("Unexpected `go version` output: {}", stdout.trim()))?;
+    if go_release_of(&go_version).is_none() {
+        anyhow::bail!("Unsupported Go version {go_version}.");
+    }
+    if let Ok(file_version) = read_goroot_version_file(goroot)
+        && file_version != go_version
+    {
+        anyhow::bail!(
+            "{} reports {file_version}, but `go version` reports {go_version}.",
+            goroot.join("VERSION").display()
+        );
+    }
+    Ok(go_version)
+}
+
+/// the version of `go version` output, such as `go1.21.5` of `go version go1.21.5 linux/amd64`,
+/// or `devel go1.23-e8ee1dc` of a custom build `go version devel go1.23-e8ee1dc Tue Jun 4 ...`.
+fn parse_go_version_output(stdout: &str) -> Option<String> {
+    let mut fields = stdout.split_whitespace().skip(2);
+    match fields.next()? {
+        "devel" => Some(format!("devel {}", fields.next()?)),
+        v if v.starts_with("go") => Some(v.to_owned()),
+        _ => None,
+    }
+}
+
+/// the release a Go version is or is developed for, such as 1.23 of `devel go1.23-e8ee1dc`.
+fn go_release_of(go_version: &str) -> Option<GoVersion> {
+    let v = go_version.strip_prefix("devel ").unwrap_or(go_version);
+    v.split(['-', ' ']).next()?.parse().ok()
+}
+
+/// read the first line of `${GOROOT}/VERSION`, such as `go1.21.5`.
+fn read_goroot_version_file(goroot: &Path) -> anyhow::Result<String> {
+    let content = fs::read_to_string(goroot.join("VERSION"))?;
+    content
+        .lines()
+        .next()
+        .map(str::trim)
+        .filter(|v| v.starts_with("go"))
+        .map(ToOwned::to_owned)
+        .ok_or_else(|| anyhow!("Invalid VERSION file in {}", goroot.display()))
+}
+
+/// create a directory symlink on unix, or a junction on windows.
+fn symlink_dir<P1, P2>(original: P1, link: P2) -> anyhow::Result<()>
+where
+    P1: AsRef<Path>,
+    P2: AsRef<Path>,
+{
     #[cfg(unix)]
     {
         use std::os::unix::fs as unix_fs;
-        unix_fs::symlink(original, &link)?;
+        unix_fs::symlink(original, link)?;
     }
     #[cfg(windows)]
     {
-        junction::create(original, &link)?;
+        junction::create(original, link)?;
     }
+    Ok(())
+}
 
-    println!("Default Go is set to '{version}'");
+/// remove a directory symlink(junction) itself if it exists, never follows it.
+pub(crate) fn remove_symlink_dir<P: AsRef<Path>>(link: P) -> anyhow::Result<()> {
+    let link = link.as_ref();
+    if link.symlink_metadata().is_ok() {
+        #[cfg(unix)]
+        fs::remove_file(link)?;
+        #[cfg(windows)]
+        fs::remove_dir(link)?;
+    }
     Ok(())
 }
 
-/// remove the go version, if it is current active go version, will ignore deletion.
+/// remove the go version, the active go version is refused, switch or unset it first.
+/// a linked toolchain is only unlinked, the linked GOROOT is left untouched.
+/// the version may be an alias, the alias itself is kept.
 pub fn remove_go_version(version: &str) -> anyhow::Result<()> {
-    let version = Version::normalize(version);
-    let version_dir = Dir::goup_home()?.version(&version);
+    let version = Version::resolve(version)?;
+    if current_go_version()?.is_some_and(|cur| cur == version) {
+        anyhow::bail!("{version} is the active version, switch to another version first.");
+    }
+    let goup_home = Dir::goup_home()?;
+    let version_dir = goup_home.version(&version);
+    if goup_home.is_dot_linked_file_exists(&version) {
+        remove_symlink_dir(goup_home.version_go(&version))?;
+    }
     if version_dir.exists() {
         fs::remove_dir_all(&version_dir)?;
     }
+    cache::remove_toolchain_modules(&goup_home, &format!("v0.0.1-{version}."))?;
 
-    if let Some(cur) = current_go_version()?
-        && cur == version
-    {
-        log::warn!("{version} is the active version.");
+    let names = Aliases::load(&goup_home)?.names_of(&version);
+    if !names.is_empty() {
+        log::warn!("{version} is targeted by alias {}.", names.join(", "));
     }
 
     Ok(())
 }
 
-/// remove multiple go version, if it is current active go version, will ignore deletion.
+/// remove multiple go version, fails at the active go version.
 pub fn remove_go_versions(vers: &[String]) -> anyhow::Result<()> {
-    let spinner = ProgressBar::new_spinner();
-    spinner.enable_steady_tick(time::Duration::from_millis(100));
+    let spinner = progress::spinner();
 
     for (i, ver) in vers.iter().enumerate() {
         spinner.set_message(format!("Removing {ver} ({}/{})", i + 1, vers.len()));
@@ -208,6 +613,28 @@ pub fn remove_go_versions(vers: &[String]) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// verify the installed files of a go version against the manifest recorded at install time.
+/// returns `None` if the version was installed without a manifest.
+pub fn verify_go_version(version: &str) -> anyhow::Result<Option<Report>> {
+    let version = Version::resolve(version)?;
+    let goup_home = Dir::goup_home()?;
+    if goup_home.is_dot_linked_file_exists(&version) {
+        return Err(anyhow!(
+            "{version} is a linked toolchain, it is not managed by goup."
+        ));
+    }
+    if !goup_home.is_dot_unpacked_success_file_exists(&version) {
+        return Err(anyhow!("{version} is not installed."));
+    }
+    let manifest_file = goup_home.version_dot_manifest(&version);
+    if !manifest_file.exists() {
+        return Ok(None);
+    }
+    let recorded = Manifest::load(manifest_file)?;
+    let actual = Manifest::build(goup_home.version_go(&version))?;
+    Ok(Some(recorded.compare(&actual)))
+}
+
 /// current active go version
 pub fn current_go_version() -> anyhow::Result<Option<String>> {
     // may be current not exist
@@ -218,6 +645,103 @@ pub fn current_go_version() -> anyhow::Result<Option<String>> {
     Ok(current)
 }
 
+/// remove `${HOME}/.goup/current`, so no go version is active.
+pub fn unset_go_version() -> anyhow::Result<()> {
+    remove_symlink_dir(Dir::goup_home()?.current())
+}
+
+/// the latest stable installed go version except the given ones, the fallback when
+/// the active version is removed.
+pub fn latest_stable_go_version(excluding: &[String]) -> anyhow::Result<Option<String>> {
+    Ok(list_go_version()?
+        .into_iter()
+        .rev()
+        .filter(|v| v.version.is_stable())
+        .filter_map(|v| v.dir_name())
+        .find(|name| !excluding.contains(name)))
+}
+
+/// where the active go version is selected from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActiveSource {
+    /// `GOUP_GO_VERSION` environment variable.
+    Env,
+    /// `.go-version` file in the working directory or its parents.
+    File(PathBuf),
+    /// `${HOME}/.goup/current` set by `goup set`.
+    Global,
+}
+
+/// the active go version and where it is selected from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveVersion {
+    // normalized version: go1.21.1
+    pub version: String,
+    pub source: ActiveSource,
+}
+
+/// the active go version. `GOUP_GO_VERSION` environment variable takes precedence
+/// over the nearest `.go-version` file, which takes precedence over `goup set`.
+pub fn active_go_version() -> anyhow::Result<Option<ActiveVersion>> {
+    if let Ok(version) = env::var(consts::GOUP_GO_VERSION)
+        && !version.trim().is_empty()
+    {
+        return Ok(Some(ActiveVersion {
+            version: resolve_installed_go_version(version.trim())?,
+            source: ActiveSource::Env,
+        }));
+    }
+    if let Some(file) = find_go_version_file(&env::current_dir()?) {
+        return Ok(Some(ActiveVersion {
+            version: resolve_installed_go_version(&read_go_version_file(&file)?)?,
+            source: ActiveSource::File(file),
+        }));
+    }
+    Ok(current_go_version()?.map(|version| ActiveVersion {
+        version,
+        source: ActiveSource::Global,
+    }))
+}
+
+/// find the nearest `.go-version` file from the directory up to the root.
+pub fn find_go_version_file(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|d| d.join(consts::GO_VERSION_FILE))
+        .find(|f| f.is_file())
+}
+
+/// read the version from the first non-empty line of a `.go-version` file.
+pub fn read_go_version_file(path: &Path) -> anyhow::Result<String> {
+    fs::read_to_string(path)?
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .map(ToOwned::to_owned)
+        .ok_or_else(|| anyhow!("{} is empty", path.display()))
+}
+
+/// absolute path of a tool of the installed go version,
+/// such as `go`, `gofmt` in `bin`, or `vet` in `pkg/tool/{os}_{arch}`.
+pub fn which_go_tool(version: &str, tool: &str) -> anyhow::Result<PathBuf> {
+    let goroot = Dir::goup_home()?.version_go(version);
+    if !goroot.exists() {
+        anyhow::bail!("Go version {version} is not installed. Install it with `goup install`.");
+    }
+    let exe = format!("{tool}{}", env::consts::EXE_SUFFIX);
+    let bin = goroot.join("bin").join(&exe);
+    if bin.is_file() {
+        return Ok(bin);
+    }
+    if let Ok(dir) = goroot.join("pkg").join("tool").read_dir() {
+        let mut tool_dirs: Vec<_> = dir.filter_map(|v| v.ok().map(|v| v.path())).collect();
+        tool_dirs.sort();
+        if let Some(p) = tool_dirs.iter().map(|d| d.join(&exe)).find(|p| p.is_file()) {
+            return Ok(p);
+        }
+    }
+    anyhow::bail!("'{tool}' is not found in Go version {version}.")
+}
+
 /// list `${HOME}/.goup/cache` directory items(only file, ignore directory).
 pub fn list_cache(contain_sha256: bool) -> anyhow::Result<Vec<String>> {
     let goup_home = Dir::goup_home()?;
@@ -258,3 +782,183 @@ pub fn remove_goup_home() -> anyhow::Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_go_version_output() {
+        let parse = |s: &str| parse_go_version_output(s);
+        assert_eq!(
+            parse("go version go1.21.5 linux/amd64\n").as_deref(),
+            Some("go1.21.5")
+        );
+        assert_eq!(
+            parse("go version devel go1.23-e8ee1dc Tue Jun 4 10:00:00 2024 +0000 linux/amd64")
+                .as_deref(),
+            Some("devel go1.23-e8ee1dc")
+        );
+        assert_eq!(parse("go version"), None);
+        assert_eq!(parse("not go at all"), None);
+
+        let release = |v: &str| go_release_of(v).map(|v| v.to_string());
+        assert_eq!(release("go1.21.5").as_deref(), Some("1.21.5"));
+        assert_eq!(release("devel go1.23-e8ee1dc").as_deref(), Some("1.23"));
+        assert_eq!(release("devel +e8ee1dc"), None);
+    }
+
+    #[test]
+    fn test_go_version_file() -> anyhow::Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        let project = tmp_dir.path().join("project");
+        let sub = project.join("cmd").join("app");
+        fs::create_dir_all(&sub)?;
+        assert_eq!(find_go_version_file(&sub), None);
+
+        let file = project.join(consts::GO_VERSION_FILE);
+        fs::write(&file, "\n  1.21.9 \n1.22.3\n")?;
+        assert_eq!(find_go_version_file(&sub), Some(file.clone()));
+        assert_eq!(find_go_version_file(&project), Some(file.clone()));
+        assert_eq!(read_go_version_file(&file)?, "1.21.9");
+
+        fs::write(&file, "\n")?;
+        assert!(read_go_version_file(&file).is_err());
+        Ok(())
+    }
+
+    fn release(version: &str, filenames: &[&str]) -> GoRelease {
+        GoRelease {
+            version: version.to_owned(),
+            stable: true,
+            files: filenames
+                .iter()
+                .map(|f| GoFile {
+                    arch: "amd64".to_owned(),
+                    filename: f.to_string(),
+                    kind: "archive".to_owned(),
+                    // go1.21.0.linux-amd64.tar.gz -> linux
+                    os: f
+                        .split('.')
+                        .find_map(|p| p.split_once('-'))
+                        .map_or("", |(os, _)| os)
+                        .to_owned(),
+                    sha256: String::new(),
+                    size: 0,
+                    version: format!("go{version}"),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_merge_release_index() -> anyhow::Result<()> {
+        let tmp_home_dir = tempfile::tempdir()?;
+        let with_prefix = |mut r: GoRelease| {
+            r.version = format!("go{}", r.version);
+            r
+        };
+        temp_env::with_var(consts::GOUP_HOME, Some(tmp_home_dir.path()), || {
+            merge_release_index(vec![with_prefix(release(
+                "1.21.9",
+                &["go1.21.9.linux-amd64.tar.gz"],
+            ))])?;
+            merge_release_index(vec![
+                with_prefix(release("1.22.3", &["go1.22.3.linux-amd64.tar.gz"])),
+                with_prefix(release(
+                    "1.21.9",
+                    &[
+                        "go1.21.9.linux-amd64.tar.gz",
+                        "go1.21.9.darwin-amd64.tar.gz",
+                    ],
+                )),
+            ])?;
+            let cached = list_cached_go_releases()?;
+            assert_eq!(
+                cached
+                    .iter()
+                    .map(|r| r.version.as_str())
+                    .collect::<Vec<_>>(),
+                ["1.21.9", "1.22.3"]
+            );
+            assert_eq!(cached[0].platforms(), ["linux/amd64", "darwin/amd64"]);
+            anyhow::Ok(())
+        })
+    }
+
+    #[test]
+    fn test_match_installed_go_versions() -> anyhow::Result<()> {
+        let tmp_home_dir = tempfile::tempdir()?;
+        let goup_home = Dir::new(tmp_home_dir.path());
+        for ver in ["go1.20", "go1.20.14", "go1.21.5", "go1.21.9", "go1.22.0"] {
+            fs::create_dir_all(goup_home.version_go(ver))?;
+            goup_home.create_dot_unpacked_success_file(ver)?;
+        }
+        // a linked toolchain, its version is read from the linked GOROOT.
+        let goroot = tmp_home_dir.path().join("system-go");
+        fs::create_dir_all(&goroot)?;
+        fs::write(goroot.join("VERSION"), "go1.20.3\n")?;
+        fs::create_dir_all(goup_home.version_go("gosystem"))?;
+        goup_home.create_dot_linked_file("gosystem", &goroot, "go1.20.3")?;
+
+        temp_env::with_var(consts::GOUP_HOME, Some(goup_home.as_os_str()), || {
+            assert_eq!(match_installed_go_versions("1.21.5")?, ["go1.21.5"]);
+            assert_eq!(
+                match_installed_go_versions("1.21")?,
+                ["go1.21.9", "go1.21.5"]
+            );
+            assert_eq!(
+                match_installed_go_versions(">=1.21.6")?,
+                ["go1.22.0", "go1.21.9"]
+            );
+            assert_eq!(match_installed_go_versions("system")?, ["gosystem"]);
+            // a bare minor release is the newest installed patch release, not the directory.
+            assert_eq!(
+                match_installed_go_versions("1.20")?,
+                ["go1.20.14", "gosystem", "go1.20"]
+            );
+            assert_eq!(match_installed_go_versions("=1.20")?, ["go1.20"]);
+            assert_eq!(match_installed_go_versions("1.20.14")?, ["go1.20.14"]);
+            assert!(match_installed_go_versions("1.19")?.is_empty());
+            assert!(match_installed_go_versions("not-a-version")?.is_empty());
+            anyhow::Ok(())
+        })
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("1.21", "1.21"), 0);
+        assert_eq!(edit_distance("1.2l", "1.21"), 1);
+        assert_eq!(edit_distance("1.21", "1.21.0"), 2);
+        assert_eq!(edit_distance("", "1.21"), 4);
+        assert_eq!(edit_distance("1.12", "1.21"), 2);
+    }
+
+    #[test]
+    fn test_check_go_release() {
+        let releases = [
+            release("1.4", &["go1.4.darwin-amd64.tar.gz"]),
+            release("1.21.0", &["go1.21.0.linux-amd64.tar.gz"]),
+            release("1.21.1", &["go1.21.1.linux-amd64.tar.gz"]),
+            release("1.22rc1", &[]),
+        ];
+        let archive = |v: &str| format!("go{v}.linux-amd64.tar.gz");
+        assert!(check_go_release("go1.21.1", &archive("1.21.1"), &releases).is_ok());
+        assert!(check_go_release("go1.22rc1", &archive("1.22rc1"), &releases).is_ok());
+
+        let err = check_go_release("go1.2l.1", &archive("1.2l.1"), &releases).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Go version 1.2l.1 does not exist, did you mean 1.21.1, 1.21.0?"
+        );
+        let err = check_go_release("go9", &archive("9"), &releases).unwrap_err();
+        assert_eq!(err.to_string(), "Go version 9 does not exist.");
+
+        let err = check_go_release("go1.4", &archive("1.4"), &releases).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Go version 1.4 is not built for this platform, go1.4.linux-amd64.tar.gz does not exist. \
+            It is built for darwin/amd64."
+        );
+    }
+}
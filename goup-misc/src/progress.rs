@@ -0,0 +1,24 @@
+use std::sync::LazyLock;
+use std::time;
+
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget};
+
+static MULTI_PROGRESS: LazyLock<MultiProgress> = LazyLock::new(MultiProgress::new);
+
+/// the shared `MultiProgress`, all progress bars should be added to it,
+/// so that log records can be printed above them without garbling.
+pub fn multi_progress() -> &'static MultiProgress {
+    &MULTI_PROGRESS
+}
+
+/// a steady ticking spinner added to the shared `MultiProgress`.
+pub fn spinner() -> ProgressBar {
+    let spinner = MULTI_PROGRESS.add(ProgressBar::new_spinner());
+    spinner.enable_steady_tick(time::Duration::from_millis(100));
+    spinner
+}
+
+/// hide all progress bars, such as in quiet mode.
+pub fn hide() {
+    MULTI_PROGRESS.set_draw_target(ProgressDrawTarget::hidden());
+}
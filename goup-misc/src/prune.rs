@@ -0,0 +1,198 @@
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{Aliases, Dir, GoVersion, consts, op};
+
+/// directories which never contain project pin files.
+const SKIP_DIRS: [&str; 3] = ["node_modules", "testdata", "vendor"];
+/// how deep the workspace roots are searched for pin files.
+const MAX_DEPTH: usize = 5;
+
+/// which installed versions `goup prune` keeps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeepPolicy {
+    /// keep the newest N versions.
+    pub keep: usize,
+    /// also keep the newest N versions of each minor release, such as 1.21.x.
+    pub per_minor: usize,
+}
+
+/// what `goup prune` does to a version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    /// kept, with the reason.
+    Keep(String),
+    Remove,
+}
+
+impl KeepPolicy {
+    /// the verdict of each version, newest first. protected versions are kept with their reason.
+    pub fn plan(
+        &self,
+        mut versions: Vec<(String, GoVersion)>,
+        protected: &BTreeMap<String, String>,
+    ) -> Vec<(String, Verdict)> {
+        versions.sort_by(|a, b| b.1.cmp(&a.1));
+        let mut minors: HashMap<(u64, u64), usize> = HashMap::new();
+        versions
+            .into_iter()
+            .enumerate()
+            .map(|(i, (name, ver))| {
+                let (major, minor) = ver.minor_release();
+                let nth_of_minor = minors.entry((major, minor)).or_default();
+                *nth_of_minor += 1;
+                let verdict = if let Some(reason) = protected.get(&name) {
+                    Verdict::Keep(reason.clone())
+                } else if i < self.keep {
+                    Verdict::Keep(format!("newest {}", self.keep))
+                } else if *nth_of_minor <= self.per_minor {
+                    Verdict::Keep(format!("newest {} of {major}.{minor}", self.per_minor))
+                } else {
+                    Verdict::Remove
+                };
+                (name, verdict)
+            })
+            .collect()
+    }
+}
+
+/// the versions which must not be pruned, with the reason: the global active version, alias
+/// targets, and the versions pinned by `.go-version` files in the `GOUP_WORKSPACES` roots.
+/// `keep_active` also protects the version active in the working directory.
+pub fn protected_go_versions(keep_active: bool) -> anyhow::Result<BTreeMap<String, String>> {
+    let mut protected = BTreeMap::new();
+    if let Some(version) = op::current_go_version()? {
+        protected.insert(version, "active".to_owned());
+    }
+    if keep_active && let Some(active) = op::active_go_version()? {
+        protected
+            .entry(active.version)
+            .or_insert_with(|| "active in the working directory".to_owned());
+    }
+    for (name, target) in Aliases::load(&Dir::goup_home()?)?.iter() {
+        protected
+            .entry(target.to_owned())
+            .or_insert_with(|| format!("alias {name}"));
+    }
+    for root in workspace_roots() {
+        for file in find_go_version_files(&root) {
+            let Ok(version) = op::read_go_version_file(&file) else {
+                continue;
+            };
+            protected
+                .entry(op::resolve_installed_go_version(&version)?)
+                .or_insert_with(|| format!("pinned by {}", file.display()));
+        }
+    }
+    Ok(protected)
+}
+
+/// the workspace roots of `GOUP_WORKSPACES`, separated like `PATH`.
+pub fn workspace_roots() -> Vec<PathBuf> {
+    env::var_os(consts::GOUP_WORKSPACES)
+        .map(|s| env::split_paths(&s).filter(|p| p.is_dir()).collect())
+        .unwrap_or_default()
+}
+
+/// find the `.go-version` files under the root, hidden and vendored directories are skipped.
+pub fn find_go_version_files(root: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    walk(root, 0, &mut files);
+    files.sort();
+    files
+}
+
+fn walk(dir: &Path, depth: usize, files: &mut Vec<PathBuf>) {
+    let file = dir.join(consts::GO_VERSION_FILE);
+    if file.is_file() {
+        files.push(file);
+    }
+    if depth >= MAX_DEPTH {
+        return;
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with('.') || SKIP_DIRS.contains(&name.as_ref()) {
+            continue;
+        }
+        if entry.file_type().is_ok_and(|t| t.is_dir()) {
+            walk(&entry.path(), depth + 1, files);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn versions(vers: &[&str]) -> Vec<(String, GoVersion)> {
+        vers.iter()
+            .map(|v| (format!("go{v}"), v.parse().unwrap()))
+            .collect()
+    }
+
+    fn removed(plan: Vec<(String, Verdict)>) -> Vec<String> {
+        plan.into_iter()
+            .filter(|(_, v)| *v == Verdict::Remove)
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    #[test]
+    fn test_keep_policy_plan() {
+        let vers = versions(&["1.20.1", "1.21.0", "1.20.2", "1.21.5", "1.22rc1", "1.19"]);
+        let mut protected = BTreeMap::new();
+
+        let policy = KeepPolicy {
+            keep: 3,
+            per_minor: 0,
+        };
+        let plan = policy.plan(vers.clone(), &protected);
+        assert_eq!(
+            plan[0],
+            ("go1.22rc1".to_owned(), Verdict::Keep("newest 3".to_owned()))
+        );
+        assert_eq!(removed(plan), ["go1.20.2", "go1.20.1", "go1.19"]);
+
+        let policy = KeepPolicy {
+            keep: 1,
+            per_minor: 1,
+        };
+        assert_eq!(
+            removed(policy.plan(vers.clone(), &protected)),
+            ["go1.21.0", "go1.20.1"]
+        );
+
+        protected.insert("go1.20.1".to_owned(), "alias prod".to_owned());
+        let plan = policy.plan(vers, &protected);
+        assert!(plan.contains(&(
+            "go1.20.1".to_owned(),
+            Verdict::Keep("alias prod".to_owned())
+        )));
+        assert_eq!(removed(plan), ["go1.21.0"]);
+    }
+
+    #[test]
+    fn test_find_go_version_files() -> anyhow::Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        let root = tmp_dir.path();
+        for dir in ["a", "b/c", ".git", "d/vendor/e", "f/g/h/i/j/k/l"] {
+            fs::create_dir_all(root.join(dir))?;
+            fs::write(root.join(dir).join(consts::GO_VERSION_FILE), "1.21.5")?;
+        }
+        assert_eq!(
+            find_go_version_files(root),
+            [
+                root.join("a").join(consts::GO_VERSION_FILE),
+                root.join("b/c").join(consts::GO_VERSION_FILE),
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,247 @@
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+use anyhow::anyhow;
+use regex::Regex;
+use semver::VersionReq;
+
+use crate::GoVersion;
+
+/// a Go pre-release in a comparator, such as `1.21rc2` of `>=1.21rc2`.
+static GO_PRERELEASE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b\d+(?:\.\d+){0,2}(?:alpha|beta|rc)\d+\b").expect("valid regex")
+});
+
+/// a requirement of Go versions.
+/// 1.21.5, 1.21rc2, =1.20    exact version
+/// 1, 1.21, 1.21.x, ~1.21     newest release of 1.21
+/// ^1.20, >=1.20, <1.22       newest release in the range
+/// latest, stable             newest release
+/// oldstable                  newest release of the previous minor release
+/// next                       newest pre-release newer than the newest release
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GoVersionReq {
+    Exact(GoVersion),
+    Range(VersionReq),
+    Stable,
+    OldStable,
+    Next,
+}
+
+impl GoVersionReq {
+    /// the newest version which satisfies the requirement.
+    pub fn select<'a, I>(&self, versions: I) -> Option<&'a GoVersion>
+    where
+        I: IntoIterator<Item = &'a GoVersion>,
+    {
+        self.filter(versions).into_iter().next()
+    }
+
+    /// the versions which satisfy the requirement, newest first.
+    /// a channel, such as `stable`, is satisfied by a single version.
+    pub fn filter<'a, I>(&self, versions: I) -> Vec<&'a GoVersion>
+    where
+        I: IntoIterator<Item = &'a GoVersion>,
+    {
+        let versions: Vec<_> = versions.into_iter().collect();
+        let stable = versions.iter().copied().filter(|v| v.is_stable()).max();
+        let mut matched: Vec<_> = match self {
+            Self::Exact(ver) => versions.into_iter().filter(|v| *v == ver).collect(),
+            // pre-releases only match a range which names a pre-release of the same version.
+            Self::Range(req) => versions
+                .into_iter()
+                .filter(|v| req.matches(&v.to_semver()))
+                .collect(),
+            Self::Stable => stable.into_iter().collect(),
+            Self::OldStable => stable
+                .and_then(|stable| {
+                    versions
+                        .into_iter()
+                        .filter(|v| v.is_stable() && v.minor_release() < stable.minor_release())
+                        .max()
+                })
+                .into_iter()
+                .collect(),
+            Self::Next => versions
+                .into_iter()
+                .filter(|v| !v.is_stable() && stable.is_none_or(|s| *v > s))
+                .max()
+                .into_iter()
+                .collect(),
+        };
+        matched.sort_by(|a, b| b.cmp(a));
+        matched
+    }
+
+    /// like [`Self::filter`], but the pre-releases of a release in the range match too,
+    /// such as 1.21rc2 of `>=1.21`.
+    pub fn filter_with_unstable<'a, I>(&self, versions: I) -> Vec<&'a GoVersion>
+    where
+        I: IntoIterator<Item = &'a GoVersion>,
+    {
+        let Self::Range(req) = self else {
+            return self.filter(versions);
+        };
+        let mut matched: Vec<_> = versions
+            .into_iter()
+            .filter(|v| {
+                let release = GoVersion {
+                    pre: None,
+                    ..(*v).clone()
+                };
+                req.matches(&v.to_semver()) || req.matches(&release.to_semver())
+            })
+            .collect();
+        matched.sort_by(|a, b| b.cmp(a));
+        matched
+    }
+}
+
+impl FromStr for GoVersionReq {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s {
+            "latest" | "stable" => return Ok(Self::Stable),
+            "oldstable" => return Ok(Self::OldStable),
+            "next" => return Ok(Self::Next),
+            _ => {}
+        }
+        if let Some(ver) = s.strip_prefix('=')
+            && let Ok(ver) = ver.parse()
+        {
+            return Ok(Self::Exact(ver));
+        }
+        if let Ok(ver) = s.parse::<GoVersion>() {
+            if ver.patch.is_some() || ver.pre.is_some() {
+                return Ok(Self::Exact(ver));
+            }
+            // a bare minor release means its newest patch release, not `^1.21`.
+            return Ok(Self::Range(VersionReq::parse(&format!(
+                "={}",
+                s.trim_start_matches("go")
+            ))?));
+        }
+        // the semver spelling of the pre-releases: 1.21rc2 -> 1.21.0-rc2
+        let s = GO_PRERELEASE_RE.replace_all(s, |caps: &regex::Captures| {
+            caps[0]
+                .parse::<GoVersion>()
+                .map_or_else(|_| caps[0].to_owned(), |v| v.to_semver().to_string())
+        });
+        VersionReq::parse(&s)
+            .map(Self::Range)
+            .map_err(|e| anyhow!("invalid version requirement '{s}': {e}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::op::GoRelease;
+
+    /// the upstream release index recorded in `testdata/releases.json`.
+    fn recorded_index() -> Vec<GoVersion> {
+        let releases: Vec<GoRelease> =
+            serde_json::from_str(include_str!("../testdata/releases.json")).unwrap();
+        releases
+            .iter()
+            .map(|r| r.version.parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_select() {
+        let index = recorded_index();
+        let cases = [
+            ("1.21.5", Some("1.21.5")),
+            ("go1.21.5", Some("1.21.5")),
+            ("=1.21.5", Some("1.21.5")),
+            ("1.21rc2", Some("1.21rc2")),
+            ("1.21.0-rc2", Some("1.21rc2")),
+            ("=1.20", Some("1.20")),
+            ("1.20", Some("1.20.14")),
+            ("1.21", Some("1.21.12")),
+            ("go1.21", Some("1.21.12")),
+            ("1", Some("1.22.5")),
+            ("1.21.x", Some("1.21.12")),
+            ("1.21.*", Some("1.21.12")),
+            ("~1.21", Some("1.21.12")),
+            ("~1.19.3", Some("1.19.13")),
+            ("^1.20", Some("1.22.5")),
+            (">=1.20, <1.22", Some("1.21.12")),
+            ("<1.20", Some("1.19.13")),
+            ("<=1.20", Some("1.20.14")),
+            (">=1.21rc2, <1.21.0", Some("1.21rc4")),
+            ("latest", Some("1.22.5")),
+            ("stable", Some("1.22.5")),
+            ("oldstable", Some("1.21.12")),
+            ("next", Some("1.23rc2")),
+            ("1.21.99", None),
+            ("1.24", None),
+            (">=2", None),
+        ];
+        for (req, expected) in cases {
+            let selected = req
+                .parse::<GoVersionReq>()
+                .unwrap_or_else(|e| panic!("{req}: {e}"))
+                .select(&index)
+                .map(ToString::to_string);
+            assert_eq!(selected.as_deref(), expected, "{req}");
+        }
+    }
+
+    #[test]
+    fn test_filter() {
+        let index = recorded_index();
+        let filter = |req: &str| -> Vec<String> {
+            req.parse::<GoVersionReq>()
+                .unwrap()
+                .filter(&index)
+                .into_iter()
+                .map(ToString::to_string)
+                .collect()
+        };
+        assert_eq!(filter("~1.22.3"), ["1.22.5", "1.22.4", "1.22.3"]);
+        assert_eq!(filter(">=1.19.12, <1.20.1"), ["1.20", "1.19.13", "1.19.12"]);
+        assert_eq!(filter("1.21rc2"), ["1.21rc2"]);
+        assert_eq!(filter("oldstable"), ["1.21.12"]);
+        assert!(filter("<1").is_empty());
+    }
+
+    #[test]
+    fn test_filter_with_unstable() {
+        let index = recorded_index();
+        let filter = |req: &str| -> Vec<String> {
+            req.parse::<GoVersionReq>()
+                .unwrap()
+                .filter_with_unstable(&index)
+                .into_iter()
+                .map(ToString::to_string)
+                .collect()
+        };
+        assert_eq!(filter(">=1.22.5"), ["1.23rc2", "1.23rc1", "1.22.5"]);
+        assert_eq!(filter("next"), ["1.23rc2"]);
+        assert_eq!(filter("1.21.5"), ["1.21.5"]);
+        assert!(filter("~1.22.3").iter().all(|v| !v.contains("rc")));
+    }
+
+    #[test]
+    fn test_select_next() {
+        // no pre-release is newer than the newest release.
+        let index: Vec<_> = recorded_index()
+            .into_iter()
+            .filter(|v| v.minor_release() < (1, 23))
+            .collect();
+        assert_eq!(GoVersionReq::Next.select(&index), None);
+        assert_eq!(GoVersionReq::Stable.select(&[]), None);
+        assert_eq!(GoVersionReq::OldStable.select(&[]), None);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        for req in ["", "foo", "1.2l", ">=1.2l", "1.21gamma1"] {
+            assert!(req.parse::<GoVersionReq>().is_err(), "{req}");
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::manifest::compute_file_sha256;
+use crate::{Dir, GoVersionReq};
+
+/// the default file name of the team toolchain manifest.
+pub const GOUP_TOML: &str = "goup.toml";
+
+/// the team toolchain manifest checked into a repository, applied by `goup sync`.
+///
+/// ```toml
+/// versions = ["1.21.9", "1.22", "oldstable"]
+/// default = "1.22"
+///
+/// [sha256]
+/// "go1.21.9.linux-amd64.tar.gz" = "f76194c2dc607e0df4ed2e7b825b5847cb37e34fc70d780e2f6c7e805634a7ea"
+///
+/// [mirror]
+/// host = "https://go.dev"
+/// download_base_url = "https://dl.google.com/go"
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GoupToml {
+    /// the required versions, exact or a requirement, see [`GoVersionReq`].
+    pub versions: Vec<String>,
+    /// the default version, it is required too.
+    pub default: Option<String>,
+    /// pinned sha256 of the archives, keyed by the archive file name.
+    pub sha256: BTreeMap<String, String>,
+    pub mirror: Mirror,
+}
+
+/// where the versions of `goup.toml` are downloaded from, the environment variables
+/// and the command line take precedence.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Mirror {
+    /// see `GOUP_GO_HOST`.
+    pub host: Option<String>,
+    /// see `GOUP_GO_DOWNLOAD_BASE_URL`.
+    pub download_base_url: Option<String>,
+}
+
+/// a pinned checksum compared with the cached archive checksum.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PinCheck {
+    /// no checksum is pinned for the archive.
+    Unpinned,
+    Match,
+    Mismatch {
+        expected: String,
+        actual: String,
+    },
+    /// the archive is not cached, it can not be verified.
+    Unknown,
+}
+
+/// what `goup sync` does.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SyncPlan {
+    /// the required versions, normalized.
+    pub required: Vec<String>,
+    /// the required versions which are not installed.
+    pub missing: Vec<String>,
+    /// the installed versions which are not required, removed by `--prune`.
+    pub extra: Vec<String>,
+    /// the default version, normalized.
+    pub default: Option<String>,
+}
+
+impl GoupToml {
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Reading {} failed: {e}", path.display()))?;
+        content.parse()
+    }
+
+    /// the checksum pinned for the archive against the checksum of the cached archive.
+    pub fn check_pinned_sha256(&self, archive: &str) -> anyhow::Result<PinCheck> {
+        let Some(expected) = self.sha256.get(archive) else {
+            return Ok(PinCheck::Unpinned);
+        };
+        let archive_file = Dir::goup_home()?.cache_file(archive);
+        if !archive_file.is_file() {
+            return Ok(PinCheck::Unknown);
+        }
+        let actual = compute_file_sha256(&archive_file)?;
+        Ok(if actual.eq_ignore_ascii_case(expected.trim()) {
+            PinCheck::Match
+        } else {
+            PinCheck::Mismatch {
+                expected: expected.to_owned(),
+                actual,
+            }
+        })
+    }
+}
+
+impl std::str::FromStr for GoupToml {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let manifest: Self = toml::from_str(s)?;
+        for ver in manifest.versions.iter().chain(&manifest.default) {
+            ver.parse::<GoVersionReq>()
+                .map_err(|e| anyhow::anyhow!("Invalid version '{ver}' in {GOUP_TOML}: {e}"))?;
+        }
+        Ok(manifest)
+    }
+}
+
+impl SyncPlan {
+    /// the plan of the resolved versions against the installed versions.
+    pub fn new(required: Vec<String>, default: Option<String>, installed: &[String]) -> Self {
+        let mut all: Vec<String> = Vec::new();
+        for ver in required.into_iter().chain(default.clone()) {
+            if !all.contains(&ver) {
+                all.push(ver);
+            }
+        }
+        Self {
+            missing: all
+                .iter()
+                .filter(|v| !installed.contains(v))
+                .cloned()
+                .collect(),
+            extra: installed
+                .iter()
+                .filter(|v| !all.contains(v))
+                .cloned()
+                .collect(),
+            required: all,
+            default,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() -> anyhow::Result<()> {
+        let manifest: GoupToml = r#"
+            versions = ["1.21.9", "1.22", "oldstable"]
+            default = "1.22"
+
+            [sha256]
+            "go1.21.9.linux-amd64.tar.gz" = "abc"
+
+            [mirror]
+            host = "https://go.dev"
+        "#
+        .parse()?;
+        assert_eq!(manifest.versions, ["1.21.9", "1.22", "oldstable"]);
+        assert_eq!(manifest.default.as_deref(), Some("1.22"));
+        assert_eq!(manifest.sha256["go1.21.9.linux-amd64.tar.gz"], "abc");
+        assert_eq!(manifest.mirror.host.as_deref(), Some("https://go.dev"));
+        assert_eq!(manifest.mirror.download_base_url, None);
+
+        assert_eq!("".parse::<GoupToml>()?, GoupToml::default());
+        assert!(r#"versions = ["1.2l"]"#.parse::<GoupToml>().is_err());
+        assert!(r#"version = ["1.21"]"#.parse::<GoupToml>().is_err());
+        Ok(())
+    }
+
+    /// sha256 of "a" and "b".
+    const SHA256_A: &str = "ca978112ca1bbdcafac231b39a23dc4da786eff8147c4e72b9807785afee48bb";
+    const SHA256_B: &str = "3e23e8160039594a33894f6564e1b1348bbd7a0088d42c4acb73eeaed59c009d";
+
+    #[test]
+    fn test_check_pinned_sha256() -> anyhow::Result<()> {
+        let tmp_home_dir = tempfile::tempdir()?;
+        let goup_home = Dir::new(tmp_home_dir);
+        fs::create_dir_all(goup_home.cache())?;
+        fs::write(goup_home.cache_file("a.tar.gz"), "a")?;
+        fs::write(goup_home.cache_file("b.tar.gz"), "b")?;
+        // a sidecar never stands for the archive.
+        fs::write(goup_home.cache_file("c.tar.gz.sha256"), SHA256_A)?;
+        let manifest: GoupToml = format!(
+            r#"
+            [sha256]
+            "a.tar.gz" = "{}"
+            "b.tar.gz" = "{SHA256_A}"
+            "c.tar.gz" = "{SHA256_A}"
+            "#,
+            SHA256_A.to_ascii_uppercase()
+        )
+        .parse()?;
+        temp_env::with_var(
+            crate::consts::GOUP_HOME,
+            Some(goup_home.as_os_str()),
+            || {
+                assert_eq!(manifest.check_pinned_sha256("a.tar.gz")?, PinCheck::Match);
+                assert_eq!(
+                    manifest.check_pinned_sha256("b.tar.gz")?,
+                    PinCheck::Mismatch {
+                        expected: SHA256_A.to_owned(),
+                        actual: SHA256_B.to_owned()
+                    }
+                );
+                assert_eq!(manifest.check_pinned_sha256("c.tar.gz")?, PinCheck::Unknown);
+                assert_eq!(
+                    manifest.check_pinned_sha256("d.tar.gz")?,
+                    PinCheck::Unpinned
+                );
+                anyhow::Ok(())
+            },
+        )
+    }
+
+    #[test]
+    fn test_sync_plan() {
+        let installed = ["go1.20.1".to_owned(), "go1.21.9".to_owned()];
+        let plan = SyncPlan::new(
+            vec!["go1.21.9".to_owned(), "go1.22.3".to_owned()],
+            Some("go1.23.0".to_owned()),
+            &installed,
+        );
+        assert_eq!(plan.required, ["go1.21.9", "go1.22.3", "go1.23.0"]);
+        assert_eq!(plan.missing, ["go1.22.3", "go1.23.0"]);
+        assert_eq!(plan.extra, ["go1.20.1"]);
+        assert_eq!(plan.default.as_deref(), Some("go1.23.0"));
+    }
+}
@@ -1,18 +1,187 @@
+use std::cmp::Ordering;
+use std::fmt;
 use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::LazyLock;
 
+use anyhow::anyhow;
+use regex::Regex;
 use semver::Version as SemVersion;
 
+use crate::Aliases;
 use crate::Dir;
 
+/// 1, 1.21, 1.21.1, 1.21rc2, go1.4beta1, and the semver like 1.21.0-rc2
+static GO_VERSION_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?:go)?(\d+)(?:\.(\d+))?(?:\.(\d+))?(?:-?(alpha|beta|rc)\.?(\d+))?$")
+        .expect("valid regex")
+});
+
+/// pre-release of a Go version, ordered alpha < beta < rc.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Prerelease {
+    Alpha(u64),
+    Beta(u64),
+    Rc(u64),
+}
+
+impl fmt::Display for Prerelease {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Prerelease::Alpha(n) => write!(f, "alpha{n}"),
+            Prerelease::Beta(n) => write!(f, "beta{n}"),
+            Prerelease::Rc(n) => write!(f, "rc{n}"),
+        }
+    }
+}
+
+/// a Go version which keeps the Go spelling, so it round-trips to the directory name
+/// and the upstream release.
+/// 1, 1.2.2, 1.4beta1, 1.20, 1.21rc2, 1.21.0
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GoVersion {
+    pub major: u64,
+    pub minor: Option<u64>,
+    pub patch: Option<u64>,
+    pub pre: Option<Prerelease>,
+}
+
+impl GoVersion {
+    /// the directory name in `GOUP_HOME`: go1.21rc2
+    pub fn dir_name(&self) -> String {
+        format!("go{self}")
+    }
+    /// a release, not a beta or a release candidate.
+    pub fn is_stable(&self) -> bool {
+        self.pre.is_none()
+    }
+    /// major and minor release, such as (1, 21) of 1.21.5.
+    pub fn minor_release(&self) -> (u64, u64) {
+        (self.major, self.minor.unwrap_or_default())
+    }
+    /// the semver to match version requirements.
+    /// 1        -> 1.0.0
+    /// 1.21     -> 1.21.0
+    /// 1.21rc2  -> 1.21.0-rc2
+    pub fn to_semver(&self) -> SemVersion {
+        let mut ver = SemVersion::new(
+            self.major,
+            self.minor.unwrap_or_default(),
+            self.patch.unwrap_or_default(),
+        );
+        if let Some(pre) = self.pre {
+            ver.pre = semver::Prerelease::new(&pre.to_string()).expect("valid pre-release");
+        }
+        ver
+    }
+}
+
+impl FromStr for GoVersion {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let caps = GO_VERSION_RE
+            .captures(s.trim())
+            .ok_or_else(|| anyhow!("invalid Go version: {s}"))?;
+        let num = |i: usize| -> anyhow::Result<Option<u64>> {
+            caps.get(i)
+                .map(|m| m.as_str().parse())
+                .transpose()
+                .map_err(Into::into)
+        };
+        let pre = match (caps.get(4).map(|m| m.as_str()), num(5)?) {
+            (Some("alpha"), Some(n)) => Some(Prerelease::Alpha(n)),
+            (Some("beta"), Some(n)) => Some(Prerelease::Beta(n)),
+            (Some("rc"), Some(n)) => Some(Prerelease::Rc(n)),
+            _ => None,
+        };
+        let mut patch = num(3)?;
+        // pre-releases have no patch: 1.21.0-rc2 -> 1.21rc2
+        if pre.is_some() && patch == Some(0) {
+            patch = None;
+        }
+        Ok(Self {
+            major: num(1)?.unwrap_or_default(),
+            minor: num(2)?,
+            patch,
+            pre,
+        })
+    }
+}
+
+impl fmt::Display for GoVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.major)?;
+        if let Some(minor) = self.minor {
+            write!(f, ".{minor}")?;
+        }
+        if let Some(patch) = self.patch {
+            write!(f, ".{patch}")?;
+        }
+        if let Some(pre) = self.pre {
+            write!(f, "{pre}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Ord for GoVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let key = |v: &Self| {
+            (
+                v.major,
+                v.minor.unwrap_or_default(),
+                v.patch.unwrap_or_default(),
+                // a release is newer than its pre-releases.
+                v.pre.is_none(),
+                v.pre,
+                // 1.20 and 1.20.0 are the same release in different spellings.
+                v.minor.is_some(),
+                v.patch.is_some(),
+            )
+        };
+        key(self).cmp(&key(other))
+    }
+}
+
+impl PartialOrd for GoVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Version {
-    // Version: 1.21.1
-    pub version: SemVersion,
+    // Version: 1.21.1, 1.21rc2
+    pub version: GoVersion,
     // active or not
     pub active: bool,
+    // GOROOT in goup home: ${GOUP_HOME}/go1.21.1/go
+    pub path: PathBuf,
+    // externally installed toolchain registered by `goup link`
+    pub linked: Option<Linked>,
+    // aliases target this version: prod, next
+    pub aliases: Vec<String>,
+}
+
+/// an externally installed Go toolchain which is linked into `GOUP_HOME`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Linked {
+    // link name: system
+    pub name: String,
+    // the linked GOROOT: /usr/lib/go
+    pub goroot: PathBuf,
 }
 
 impl Version {
+    /// the directory name in `GOUP_HOME`, such as `go1.21.1` or `gosystem` of a linked toolchain.
+    pub fn dir_name(&self) -> Option<String> {
+        self.path
+            .parent()
+            .and_then(|p| p.file_name())
+            .map(|p| p.to_string_lossy().to_string())
+    }
     /// initializes the environment file.
     pub fn init_env(s: &str) -> anyhow::Result<()> {
         let goup_home = Dir::goup_home()?;
@@ -24,96 +193,142 @@ impl Version {
         Ok(())
     }
 
-    /// normalize the version string.
-    /// 1.21.1   -> go1.21.1
-    /// go1.21.1 -> go1.21.1
-    /// tip      -> gotip
-    /// gotip    -> gotip
+    /// normalize the version string to the directory name in `GOUP_HOME`.
+    /// 1.21.1      -> go1.21.1
+    /// go1.21.1    -> go1.21.1
+    /// 1.21.0-rc2  -> go1.21rc2
+    /// tip         -> gotip
+    /// gotip       -> gotip
     pub fn normalize(ver: &str) -> String {
-        if ver.starts_with("go") {
+        if let Ok(v) = ver.parse::<GoVersion>() {
+            v.dir_name()
+        } else if ver.starts_with("go") {
             ver.to_string()
         } else {
             format!("go{ver}")
         }
     }
-    /// semantic go version string.
-    /// 1           -> 1.0.0
-    /// 1.21        -> 1.21.0
-    /// 1.21rc2     -> 1.21.0-rc2
-    /// 1.21.1rc2   -> 1.21.1-rc2
-    /// 1.21-rc2    -> 1.21.0-rc2
-    /// 1.21.1-rc2  -> 1.21.1-rc2
-    /// 1.21.1      -> 1.21.1
-    pub fn semantic(ver: &str) -> anyhow::Result<SemVersion> {
-        let count_dot = |name: &str| name.chars().filter(|&v| v == '.').count();
-        let name = ver
-            .find("alpha")
-            .or_else(|| ver.find("beta"))
-            .or_else(|| ver.find("rc"))
-            .map_or_else(
-                || match count_dot(ver) {
-                    0 => format!("{ver}.0.0"),
-                    1 => format!("{ver}.0"),
-                    _ => ver.to_string(),
-                },
-                |idx| {
-                    let start = &ver[..idx].trim_end_matches('-');
-                    if count_dot(start) == 2 {
-                        format!("{}-{}", start, &ver[idx..])
-                    } else {
-                        format!("{}.0-{}", start, &ver[idx..])
-                    }
-                },
-            );
-        Ok(SemVersion::parse(&name)?)
+    /// resolve an alias to the version it targets, otherwise normalize the version string.
+    /// prod     -> go1.21.9 (`goup alias prod 1.21.9`)
+    /// 1.21.1   -> go1.21.1
+    pub fn resolve(ver: &str) -> anyhow::Result<String> {
+        let aliases = Aliases::load(&Dir::goup_home()?)?;
+        Ok(aliases
+            .get(ver)
+            .map_or_else(|| Self::normalize(ver), ToOwned::to_owned))
+    }
+    /// release channel of the go version string.
+    /// 1.21.1      -> stable
+    /// 1.21rc2     -> unstable
+    /// 1.21beta1   -> beta
+    pub fn channel(ver: &str) -> &'static str {
+        if ver.contains("beta") || ver.contains("alpha") {
+            "beta"
+        } else if ver.contains("rc") {
+            "unstable"
+        } else {
+            "stable"
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Version;
+    use super::{GoVersion, Prerelease, Version};
     use semver::Version as SemVersion;
 
     #[test]
     fn test_normalize() {
         assert_eq!(Version::normalize("1.21.1"), "go1.21.1",);
         assert_eq!(Version::normalize("go1.21.1"), "go1.21.1",);
+        assert_eq!(Version::normalize("1.21.0-rc2"), "go1.21rc2",);
+        assert_eq!(Version::normalize("1.20"), "go1.20",);
         assert_eq!(Version::normalize("tip"), "gotip",);
         assert_eq!(Version::normalize("gotip"), "gotip",);
     }
 
     #[test]
-    fn test_semantic() {
+    fn test_channel() {
+        assert_eq!(Version::channel("1.21.1"), "stable");
+        assert_eq!(Version::channel("go1.21.1"), "stable");
+        assert_eq!(Version::channel("1.21rc2"), "unstable");
+        assert_eq!(Version::channel("1.21.0-rc2"), "unstable");
+        assert_eq!(Version::channel("1.21beta1"), "beta");
+    }
+
+    #[test]
+    fn test_to_semver() {
         assert_eq!(
-            Version::semantic("1").unwrap(),
+            "1".parse::<GoVersion>().unwrap().to_semver(),
             "1.0.0".parse::<SemVersion>().unwrap(),
         );
         assert_eq!(
-            Version::semantic("1.21").unwrap(),
+            "1.21".parse::<GoVersion>().unwrap().to_semver(),
             "1.21.0".parse::<SemVersion>().unwrap(),
         );
         assert_eq!(
-            Version::semantic("1.21rc2").unwrap(),
+            "1.21rc2".parse::<GoVersion>().unwrap().to_semver(),
             "1.21.0-rc2".parse::<SemVersion>().unwrap(),
         );
         assert_eq!(
-            Version::semantic("1.21.1rc2").unwrap(),
+            "1.21.1rc2".parse::<GoVersion>().unwrap().to_semver(),
             "1.21.1-rc2".parse::<SemVersion>().unwrap(),
         );
         assert_eq!(
-            Version::semantic("1.21-rc2").unwrap(),
+            "1.21-rc2".parse::<GoVersion>().unwrap().to_semver(),
             "1.21.0-rc2".parse::<SemVersion>().unwrap(),
         );
         assert_eq!(
-            Version::semantic("1.21.1-rc2").unwrap(),
+            "1.21.1-rc2".parse::<GoVersion>().unwrap().to_semver(),
             "1.21.1-rc2".parse::<SemVersion>().unwrap(),
         );
         assert_eq!(
-            Version::semantic("1.21.1").unwrap(),
+            "1.21.1".parse::<GoVersion>().unwrap().to_semver(),
             "1.21.1".parse::<SemVersion>().unwrap(),
         );
     }
 
+    #[test]
+    fn test_go_version() {
+        let v: GoVersion = "go1.21rc2".parse().unwrap();
+        assert_eq!(
+            v,
+            GoVersion {
+                major: 1,
+                minor: Some(21),
+                patch: None,
+                pre: Some(Prerelease::Rc(2)),
+            }
+        );
+        assert_eq!(v.dir_name(), "go1.21rc2");
+        assert!(!v.is_stable());
+        assert_eq!("1.21.0-rc2".parse::<GoVersion>().unwrap(), v);
+        assert_eq!("1.21-rc.2".parse::<GoVersion>().unwrap(), v);
+        assert_eq!("1.20".parse::<GoVersion>().unwrap().to_string(), "1.20");
+        assert_eq!("1.20.0".parse::<GoVersion>().unwrap().to_string(), "1.20.0");
+        assert!("1.2l".parse::<GoVersion>().is_err());
+        assert!("tip".parse::<GoVersion>().is_err());
+        assert!("1.21gamma1".parse::<GoVersion>().is_err());
+
+        let ordered = [
+            "1.21beta1",
+            "1.21rc1",
+            "1.21rc2",
+            "1.21.0",
+            "1.21.1",
+            "1.22rc1",
+        ];
+        for w in ordered.windows(2) {
+            let (a, b) = (w[0].parse::<GoVersion>().unwrap(), w[1].parse().unwrap());
+            assert!(a < b, "{a} < {b}");
+        }
+        let (a, b) = (
+            "1.20".parse::<GoVersion>().unwrap(),
+            "1.20.0".parse().unwrap(),
+        );
+        assert!(a < b && a != b);
+    }
+
     #[test]
     fn test_all_go_version_semantic() {
         let go_versions = [
@@ -427,8 +642,13 @@ mod tests {
             "1.24rc3",
             "1.24.0",
         ];
+        let mut parsed = Vec::new();
         for ver in go_versions {
-            assert!(Version::semantic(ver).is_ok())
+            let v: GoVersion = ver.parse().unwrap();
+            assert_eq!(v.to_string(), ver);
+            parsed.push(v);
         }
+        // the upstream order
+        assert!(parsed.windows(2).all(|w| w[0] < w[1]));
     }
 }
@@ -0,0 +1,48 @@
+use goup_misc::{Aliases, Dir, Version};
+
+pub(super) fn run(
+    name: Option<String>,
+    target: Option<String>,
+    delete: bool,
+) -> anyhow::Result<()> {
+    let goup_home = Dir::goup_home()?;
+    let mut aliases = Aliases::load(&goup_home)?;
+    let Some(name) = name else {
+        if aliases.is_empty() {
+            println!("No alias is set.");
+        }
+        for (name, version) in aliases.iter() {
+            println!("{name} -> {version}");
+        }
+        return Ok(());
+    };
+
+    match target {
+        None if delete => {
+            let version = aliases
+                .remove(&name)
+                .ok_or_else(|| anyhow::anyhow!("Alias '{name}' is not set."))?;
+            aliases.save(&goup_home)?;
+            println!("Removed alias '{name}' -> {version}");
+        }
+        None => {
+            let version = aliases
+                .get(&name)
+                .ok_or_else(|| anyhow::anyhow!("Alias '{name}' is not set."))?;
+            println!("{name} -> {version}");
+        }
+        Some(target) => {
+            if goup_home.version(Version::normalize(&name)).exists() {
+                anyhow::bail!("'{name}' is already a linked toolchain.");
+            }
+            aliases.set(&name, &target)?;
+            aliases.save(&goup_home)?;
+            let version = aliases.get(&name).unwrap_or_default();
+            println!("Alias '{name}' -> {version}");
+            if !goup_home.version_go(version).exists() {
+                println!(" {version} is not installed, install it with `goup install {name}`.");
+            }
+        }
+    }
+    Ok(())
+}
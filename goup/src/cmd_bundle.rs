@@ -0,0 +1,61 @@
+use std::path::PathBuf;
+
+use clap::Subcommand;
+use indicatif::HumanBytes;
+
+use goup_misc::consts;
+
+#[derive(Subcommand, Debug, PartialEq)]
+pub(super) enum BundleCommand {
+    /// Pack the archives of the versions, their sha256 and the release index into a tar file
+    /// for an offline machine.
+    Create {
+        /// the versions, exact or a requirement, such as '1.21.9,1.22'.
+        #[arg(long, required = true, value_delimiter = ',')]
+        versions: Vec<String>,
+        /// the platforms, such as 'linux/amd64,linux/arm64', the current platform if not given.
+        #[arg(long, value_delimiter = ',')]
+        platforms: Vec<String>,
+        /// the bundle file, `--output` is the global output format.
+        #[arg(short = 'o', long = "file", default_value = "go-bundle.tar")]
+        file: PathBuf,
+        /// host that is used to fetch the release index.
+        #[arg(long, default_value_t = consts::GO_HOST.to_owned(), env = consts::GOUP_GO_HOST)]
+        host: String,
+    },
+    /// Seed the cache and the release index with a bundle, so `goup install` works offline.
+    Import {
+        /// the bundle file.
+        bundle: PathBuf,
+    },
+}
+
+pub(super) fn run(command: BundleCommand) -> anyhow::Result<()> {
+    match command {
+        BundleCommand::Create {
+            versions,
+            platforms,
+            file,
+            host,
+        } => {
+            let r = goup_downloader::create_bundle(&versions, &platforms, &host, &file)?;
+            println!(
+                "Packed {} archive(s) of {} into {}, {}",
+                r.archives,
+                r.versions.join(", "),
+                file.display(),
+                HumanBytes(r.bytes)
+            );
+        }
+        BundleCommand::Import { bundle } => {
+            let r = goup_downloader::import_bundle(&bundle)?;
+            println!(
+                "Imported {} archive(s) of {}, {}",
+                r.archives,
+                r.versions.join(", "),
+                HumanBytes(r.bytes)
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use std::env;
+use std::time::Duration;
+
+use clap::Subcommand;
+use indicatif::{HumanBytes, HumanDuration};
+
+use goup_misc::cache::{self, CacheEntry, PrunePolicy};
+use goup_misc::{Version, consts};
+
+use crate::output::{self, CacheItem, Output};
+
+#[derive(Subcommand, Debug, PartialEq)]
+pub(super) enum CacheCommand {
+    /// List the downloaded archives, with size, age and whether the version is installed.
+    #[command(visible_alias = "ls")]
+    List,
+    /// Remove the downloaded archives of the versions.
+    #[command(visible_alias = "rm")]
+    Remove {
+        /// target go version list.
+        #[arg(required = true)]
+        target: Vec<String>,
+    },
+    /// Remove the downloaded archives by the retention policy, all archives without any option.
+    Prune {
+        /// keep the archives whose version is installed.
+        #[arg(long, default_value_t = false)]
+        keep_installed: bool,
+        /// only remove the archives older than it, such as '30d', '12h' or '2w'.
+        #[arg(long, value_parser = cache::parse_duration)]
+        older_than: Option<Duration>,
+        /// remove the oldest archives until the cache is not larger than it, such as '2G' or '500M'.
+        #[arg(long, value_parser = cache::parse_size)]
+        max_size: Option<u64>,
+        /// only print the archives to remove.
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+}
+
+pub(super) fn run(command: CacheCommand, output: Output) -> anyhow::Result<()> {
+    match command {
+        CacheCommand::List => list(output),
+        CacheCommand::Remove { target } => remove(&target),
+        CacheCommand::Prune {
+            keep_installed,
+            older_than,
+            max_size,
+            dry_run,
+        } => {
+            let policy = PrunePolicy {
+                keep_installed,
+                older_than,
+                max_size,
+            };
+            if dry_run {
+                let entries = cache::list()?;
+                for e in policy.select(&entries) {
+                    println!("Would remove {} ({})", e.archive, HumanBytes(e.size));
+                }
+                return Ok(());
+            }
+            print_pruned(&cache::prune(&policy)?);
+            Ok(())
+        }
+    }
+}
+
+fn list(output: Output) -> anyhow::Result<()> {
+    let entries = cache::list()?;
+    if output == Output::Json {
+        let items: Vec<_> = entries
+            .into_iter()
+            .map(|e| CacheItem {
+                version: e.version.trim_start_matches("go").to_owned(),
+                size: e.size,
+                age: e.age().as_secs(),
+                installed: e.installed,
+                archive: e.archive,
+            })
+            .collect();
+        return output::print_json(&items);
+    }
+
+    if entries.is_empty() {
+        println!("No archive is cached.");
+        return Ok(());
+    }
+    for e in &entries {
+        println!(
+            "{:<40} {:>10} {:>12} ago{}",
+            e.archive,
+            HumanBytes(e.size).to_string(),
+            HumanDuration(e.age()).to_string(),
+            if e.installed { " (installed)" } else { "" }
+        );
+    }
+    let total: u64 = entries.iter().map(|e| e.size).sum();
+    println!("{} archive(s), {}", entries.len(), HumanBytes(total));
+    Ok(())
+}
+
+fn remove(target: &[String]) -> anyhow::Result<()> {
+    let entries = cache::list()?;
+    for ver in target {
+        let version = Version::resolve(ver)?;
+        let matched: Vec<_> = entries.iter().filter(|e| e.version == version).collect();
+        if matched.is_empty() {
+            anyhow::bail!("no archive of {version} is cached");
+        }
+        for e in matched {
+            cache::remove(e)?;
+            println!("Removed {}", e.archive);
+        }
+    }
+    Ok(())
+}
+
+fn print_pruned(removed: &[CacheEntry]) {
+    let size: u64 = removed.iter().map(|e| e.size).sum();
+    println!(
+        "Pruned {} archive(s), freed {}",
+        removed.len(),
+        HumanBytes(size)
+    );
+}
+
+/// prune the cache by `GOUP_CACHE_PRUNE` after a successful install, if it is set.
+pub(super) fn auto_prune() -> anyhow::Result<()> {
+    let Ok(policy) = env::var(consts::GOUP_CACHE_PRUNE) else {
+        return Ok(());
+    };
+    if policy.is_empty() {
+        return Ok(());
+    }
+    let removed = cache::prune(&policy.parse()?)?;
+    if !removed.is_empty() {
+        print_pruned(&removed);
+    }
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use std::env;
+use std::io;
+
+use clap_complete::Shell;
+use clap_complete::engine::CompletionCandidate;
+use clap_complete::env::Shells;
+
+use goup_misc::{Aliases, Dir, op};
+
+/// environment variable which asks goup for completions, used by the registration script.
+pub(super) const COMPLETE: &str = "COMPLETE";
+
+pub(super) fn run(shell: Shell) -> anyhow::Result<()> {
+    let shells = Shells::builtins();
+    let completer = shells
+        .completer(&shell.to_string())
+        .ok_or_else(|| anyhow::anyhow!("unsupported shell {shell}"))?;
+    let exe = env::current_exe()?;
+    completer.write_registration(
+        COMPLETE,
+        "goup",
+        "goup",
+        &exe.to_string_lossy(),
+        &mut io::stdout(),
+    )?;
+    Ok(())
+}
+
+/// installed versions, linked toolchains and aliases.
+pub(super) fn installed_versions() -> Vec<CompletionCandidate> {
+    let mut candidates: Vec<_> = op::list_go_version()
+        .unwrap_or_default()
+        .into_iter()
+        .rev()
+        .map(|v| match v.linked {
+            Some(linked) => CompletionCandidate::new(linked.name)
+                .help(Some(format!("linked {}", v.version).into())),
+            None => CompletionCandidate::new(v.version.to_string()),
+        })
+        .collect();
+    candidates.extend(aliases());
+    candidates
+}
+
+/// toolchain names, channels, aliases and versions of the cached release index,
+/// never fetches from upstream while completing.
+pub(super) fn upstream_versions() -> Vec<CompletionCandidate> {
+    let mut candidates: Vec<_> = ["stable", "unstable", "beta", "latest", "oldstable", "next"]
+        .into_iter()
+        .map(CompletionCandidate::new)
+        .collect();
+    candidates.extend(aliases());
+    candidates.extend(
+        op::list_cached_go_releases()
+            .unwrap_or_default()
+            .into_iter()
+            .rev()
+            .map(|v| CompletionCandidate::new(v.version)),
+    );
+    candidates
+}
+
+/// alias names.
+pub(super) fn aliases() -> Vec<CompletionCandidate> {
+    Dir::goup_home()
+        .and_then(|goup_home| Aliases::load(&goup_home))
+        .map(|aliases| {
+            aliases
+                .iter()
+                .map(|(name, version)| {
+                    CompletionCandidate::new(name).help(Some(format!("alias of {version}").into()))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,65 @@
+use goup_misc::Dir;
+use goup_misc::op::{self, ActiveSource};
+
+use crate::output::{self, CurrentItem, Output};
+
+pub(super) fn run(output: Output) -> anyhow::Result<()> {
+    let active = op::active_go_version()?
+        .ok_or_else(|| anyhow::anyhow!("No Go is active, set it with `goup set`."))?;
+    let path = Dir::goup_home()?.version_go(&active.version);
+    let name = active
+        .version
+        .strip_prefix("go")
+        .unwrap_or(&active.version)
+        .to_owned();
+    if !path.exists() {
+        anyhow::bail!(
+            "Go version {} is selected by {}, but it is not installed. Install it with `goup install {name}`.",
+            active.version,
+            describe(&active.source),
+        );
+    }
+
+    // only `goup set` switches `${GOUP_HOME}/current/bin` on PATH.
+    let global = op::current_go_version()?;
+    let applied = global.as_deref() == Some(active.version.as_str());
+    let global = global.map(|v| v.strip_prefix("go").unwrap_or(&v).to_owned());
+
+    if output == Output::Json {
+        let (source, origin) = match active.source {
+            ActiveSource::Env => ("env", None),
+            ActiveSource::File(file) => ("file", Some(file)),
+            ActiveSource::Global => ("global", None),
+        };
+        return output::print_json(&CurrentItem {
+            version: name,
+            source: source.to_owned(),
+            origin,
+            path: path.to_path_buf(),
+            applied,
+            global,
+        });
+    }
+
+    if applied {
+        println!("{name} (set by {})", describe(&active.source));
+    } else {
+        println!(
+            "{name} (set by {}, not applied: the Go on PATH is {})",
+            describe(&active.source),
+            global.as_deref().unwrap_or("none"),
+        );
+    }
+    Ok(())
+}
+
+fn describe(source: &ActiveSource) -> String {
+    match source {
+        ActiveSource::Env => format!(
+            "{} environment variable",
+            goup_misc::consts::GOUP_GO_VERSION
+        ),
+        ActiveSource::File(file) => file.display().to_string(),
+        ActiveSource::Global => "`goup set`".to_owned(),
+    }
+}
@@ -0,0 +1,32 @@
+use indicatif::HumanBytes;
+
+use goup_misc::dedupe;
+
+pub(super) fn run(undo: Option<Vec<String>>, dry_run: bool) -> anyhow::Result<()> {
+    match undo {
+        Some(target) => {
+            let mut files = 0;
+            let mut bytes = 0;
+            if target.is_empty() {
+                let r = dedupe::undedupe_go_versions()?;
+                (files, bytes) = (r.files, r.bytes);
+            }
+            for ver in &target {
+                let r = dedupe::undedupe_go_version(ver)?;
+                files += r.files;
+                bytes += r.bytes;
+            }
+            println!("Copied back {files} file(s), {}", HumanBytes(bytes));
+        }
+        None => {
+            let r = dedupe::dedupe_go_versions(dry_run)?;
+            let verb = if dry_run { "Would link" } else { "Linked" };
+            println!(
+                "{verb} {} identical file(s), saved {}",
+                r.files,
+                HumanBytes(r.bytes)
+            );
+        }
+    }
+    Ok(())
+}
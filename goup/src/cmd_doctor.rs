@@ -0,0 +1,41 @@
+use colored::Colorize;
+
+use goup_misc::doctor::{self, Status};
+
+pub(super) fn run(fix: bool) -> anyhow::Result<()> {
+    #[cfg(windows)]
+    colored::control::set_virtual_terminal(true).unwrap();
+
+    let diagnoses = doctor::diagnose()?;
+    let mut problems = 0;
+    let mut fixable = false;
+    for d in &diagnoses {
+        let status = match d.status {
+            Status::Pass => d.status.to_string().green(),
+            Status::Warn => d.status.to_string().yellow(),
+            Status::Fail => d.status.to_string().red(),
+        };
+        println!("[{status}] {}: {}", d.subject, d.message);
+        if fix && !d.fixes.is_empty() {
+            for f in &d.fixes {
+                f.apply()?;
+                println!("       fixed: {f}");
+            }
+            continue;
+        }
+        if let Some(hint) = &d.hint {
+            println!("       hint: {hint}");
+        }
+        fixable |= !d.fixes.is_empty();
+        if d.status == Status::Fail {
+            problems += 1;
+        }
+    }
+    if fixable {
+        println!("Run `goup doctor --fix` to apply the safe repairs.");
+    }
+    if problems > 0 {
+        anyhow::bail!("{problems} problem(s) found.");
+    }
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use std::cmp::Reverse;
+
+use clap::ValueEnum;
+use indicatif::HumanBytes;
+
+use goup_misc::du;
+
+use crate::output::{self, DuItem, DuReport, Output};
+
+/// the order of `goup du`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub(super) enum SortBy {
+    /// versions first, then the caches.
+    #[default]
+    Name,
+    /// largest first.
+    Size,
+}
+
+pub(super) fn run(sort: SortBy, output: Output) -> anyhow::Result<()> {
+    let mut du = du::disk_usage()?;
+    if sort == SortBy::Size {
+        du.items.sort_by_key(|v| Reverse(v.size));
+    }
+
+    if output == Output::Json {
+        let report = DuReport {
+            items: du
+                .items
+                .into_iter()
+                .map(|v| DuItem {
+                    name: v.name,
+                    kind: v.kind.as_str().to_owned(),
+                    path: v.path,
+                    size: v.size,
+                    shared: v.shared,
+                })
+                .collect(),
+            total: du.total,
+            disk: du.disk,
+        };
+        return output::print_json(&report);
+    }
+
+    for v in &du.items {
+        let shared = if v.shared > 0 {
+            format!(" ({} shared)", HumanBytes(v.shared))
+        } else {
+            String::new()
+        };
+        println!(
+            "{:>12}  {:<14} {}{shared}",
+            HumanBytes(v.size).to_string(),
+            v.kind.as_str(),
+            v.name
+        );
+    }
+    println!("{:>12}  total", HumanBytes(du.total).to_string());
+    if du.disk != du.total {
+        println!(
+            "{:>12}  on disk, hard linked files are counted once",
+            HumanBytes(du.disk).to_string()
+        );
+    }
+    Ok(())
+}
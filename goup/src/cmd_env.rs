@@ -1,5 +1,9 @@
+use std::env;
+
 use goup_misc::{Dir, consts};
 
+use crate::output::{self, EnvItem, Output};
+
 #[inline]
 fn print_env(key: &str, value: &str) {
     #[cfg(windows)]
@@ -8,16 +12,58 @@ fn print_env(key: &str, value: &str) {
     println!("{}={}", key, value);
 }
 
-pub(super) fn run() -> anyhow::Result<()> {
-    print_env(
-        consts::GOUP_HOME,
-        &Dir::goup_home().unwrap_or_default().to_string_lossy(),
-    );
-    print_env(consts::GOUP_GO_HOST, &consts::go_host());
-    print_env(
-        consts::GOUP_GO_DOWNLOAD_BASE_URL,
-        &consts::go_download_base_url(),
-    );
+pub(super) fn run(output: Output) -> anyhow::Result<()> {
+    let envs = [
+        (
+            consts::GOUP_HOME,
+            Dir::goup_home()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+        ),
+        (consts::GOUP_GO_HOST, consts::go_host()),
+        (
+            consts::GOUP_GO_DOWNLOAD_BASE_URL,
+            consts::go_download_base_url(),
+        ),
+        (
+            consts::GOUP_GO_VERSION,
+            env::var(consts::GOUP_GO_VERSION).unwrap_or_default(),
+        ),
+        (
+            consts::GOUP_CACHE_DIR,
+            env::var(consts::GOUP_CACHE_DIR).unwrap_or_default(),
+        ),
+        (
+            consts::GOUP_CACHE_PRUNE,
+            env::var(consts::GOUP_CACHE_PRUNE).unwrap_or_default(),
+        ),
+        (
+            consts::GOUP_WORKSPACES,
+            env::var(consts::GOUP_WORKSPACES).unwrap_or_default(),
+        ),
+    ];
+
+    if output == Output::Json {
+        let items: Vec<_> = envs
+            .into_iter()
+            .map(|(key, value)| EnvItem {
+                key: key.to_owned(),
+                value,
+                source: if consts::is_var_set(key) {
+                    "env"
+                } else {
+                    "default"
+                }
+                .to_owned(),
+            })
+            .collect();
+        return output::print_json(&items);
+    }
+
+    for (key, value) in envs {
+        print_env(key, &value);
+    }
 
     Ok(())
 }
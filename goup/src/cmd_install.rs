@@ -1,32 +1,194 @@
+use std::thread;
+
+use goup_misc::Aliases;
+use goup_misc::Dir;
 use goup_misc::Toolchain;
-use goup_misc::ToolchainFilter;
 use goup_misc::Version;
-use goup_misc::op;
-
-pub(super) fn run(toolchain: String, host: &str) -> anyhow::Result<()> {
-    let version = match toolchain.parse()? {
-        Toolchain::Stable => op::get_upstream_latest_go_version(host)?,
-        Toolchain::Unstable => {
-            let version =
-                op::list_upstream_go_versions_filter(host, Some(ToolchainFilter::Unstable))?;
-            let version = version
-                .last()
-                .ok_or_else(|| anyhow::anyhow!("failed get latest unstable version"))?;
-            version.to_string()
-        }
-        Toolchain::Beta => {
-            let version = op::list_upstream_go_versions_filter(host, Some(ToolchainFilter::Beta))?;
-            let version = version
-                .last()
-                .ok_or_else(|| anyhow::anyhow!("failed get latest beta version"))?;
-            version.to_string()
-        }
-        Toolchain::Version(ver_req) => op::match_version_req(host, &ver_req)?,
-        Toolchain::Nightly => {
-            anyhow::bail!("gotip is no supported");
-        }
-    };
-
-    let version = Version::normalize(&version);
-    goup_downloader::install_go_version(&version)
+use goup_misc::op::{self, GoRelease};
+
+use crate::{cmd_cache, cmd_dedupe};
+
+pub(super) fn run(
+    toolchains: Vec<String>,
+    host: &str,
+    dedupe: bool,
+    use_version: bool,
+) -> anyhow::Result<()> {
+    match toolchains.as_slice() {
+        [toolchain] => {
+            let version = install(toolchain.to_owned(), host)?;
+            if use_version {
+                op::set_go_version(&version)?;
+            }
+        }
+        _ if use_version => anyhow::bail!("--use takes a single toolchain."),
+        _ => install_many(&toolchains, host)?,
+    }
+    if dedupe {
+        cmd_dedupe::run(None, false)?;
+    }
+    cmd_cache::auto_prune()
+}
+
+/// install the toolchain, returns the installed version, such as `go1.21.5`.
+pub(super) fn install(toolchain: String, host: &str) -> anyhow::Result<String> {
+    let version = Resolver::new(host).resolve(&toolchain)?;
+    goup_downloader::install_go_version(&version)?;
+    Ok(version)
+}
+
+/// install the toolchains concurrently, the duplicated versions are installed once.
+/// every toolchain is reported, it fails if any toolchain failed.
+pub(super) fn install_many(toolchains: &[String], host: &str) -> anyhow::Result<()> {
+    let mut resolver = Resolver::new(host);
+    let mut report = Vec::new();
+    let mut versions: Vec<String> = Vec::new();
+    for toolchain in toolchains {
+        match resolver.resolve(toolchain) {
+            Ok(version) => {
+                if !versions.contains(&version) {
+                    versions.push(version);
+                }
+            }
+            Err(e) => report.push((toolchain.to_owned(), Err(e))),
+        }
+    }
+
+    let results: Vec<_> = thread::scope(|s| {
+        let handles: Vec<_> = versions
+            .iter()
+            .map(|version| s.spawn(move || goup_downloader::install_go_version(version)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| {
+                h.join()
+                    .unwrap_or_else(|_| Err(anyhow::anyhow!("installing panicked")))
+            })
+            .collect()
+    });
+    report.extend(versions.into_iter().zip(results));
+
+    let width = report
+        .iter()
+        .map(|(v, _)| v.len())
+        .max()
+        .unwrap_or_default();
+    let mut failed = 0;
+    for (version, result) in &report {
+        match result {
+            Ok(()) => println!("{version:<width$}  ok"),
+            Err(e) => {
+                failed += 1;
+                println!("{version:<width$}  failed: {e}");
+            }
+        }
+    }
+    if failed > 0 {
+        anyhow::bail!(
+            "{failed} of {} toolchain(s) failed to install.",
+            report.len()
+        );
+    }
+    Ok(())
+}
+
+/// resolves toolchains to versions, the upstream release index is fetched at most once.
+pub(super) struct Resolver<'a> {
+    host: &'a str,
+    releases: Option<Vec<GoRelease>>,
+}
+
+impl<'a> Resolver<'a> {
+    pub(super) fn new(host: &'a str) -> Self {
+        Self {
+            host,
+            releases: None,
+        }
+    }
+
+    /// the upstream releases, oldest first. the cached release index is used offline,
+    /// such as seeded by `goup bundle import`.
+    fn releases(&mut self) -> anyhow::Result<&[GoRelease]> {
+        if self.releases.is_none() {
+            let releases = match op::list_upstream_go_releases(self.host) {
+                Ok(releases) => releases,
+                Err(e) => {
+                    let cached = op::list_cached_go_releases()?;
+                    if cached.is_empty() {
+                        return Err(e);
+                    }
+                    log::warn!("Fetching the release index failed, the cached one is used: {e}");
+                    cached
+                }
+            };
+            self.releases = Some(releases);
+        }
+        Ok(self.releases.as_deref().unwrap_or_default())
+    }
+
+    /// the upstream versions, oldest first.
+    fn versions(&mut self) -> anyhow::Result<Vec<String>> {
+        Ok(self
+            .releases()?
+            .iter()
+            .map(|r| r.version.to_owned())
+            .collect())
+    }
+
+    /// the newest upstream version of the channel, see [`Version::channel`].
+    fn newest_of_channel(&mut self, channel: &str) -> anyhow::Result<String> {
+        self.versions()?
+            .into_iter()
+            .rfind(|v| Version::channel(v) == channel)
+            .ok_or_else(|| anyhow::anyhow!("failed get latest {channel} version"))
+    }
+
+    /// the normalized version of the toolchain, validated with the release index.
+    pub(super) fn resolve(&mut self, toolchain: &str) -> anyhow::Result<String> {
+        if let Some(version) = Aliases::load(&Dir::goup_home()?)?.get(toolchain) {
+            let version = version.to_owned();
+            self.check_release(&version)?;
+            return Ok(version);
+        }
+        let version = match toolchain.parse()? {
+            Toolchain::Stable => op::match_version_req_with("stable", || self.versions())?,
+            Toolchain::Unstable => self.newest_of_channel("unstable")?,
+            Toolchain::Beta => self.newest_of_channel("beta")?,
+            Toolchain::Version(ver_req) => {
+                op::match_version_req_with(&ver_req, || self.versions())?
+            }
+            Toolchain::Nightly => {
+                anyhow::bail!("gotip is no supported");
+            }
+        };
+
+        let version = Version::normalize(&version);
+        self.check_release(&version)?;
+        Ok(version)
+    }
+
+    /// validate the version with the cached release index before downloading,
+    /// the index is refreshed from upstream if the version is unknown to it.
+    fn check_release(&mut self, version: &str) -> anyhow::Result<()> {
+        if Dir::goup_home()?.is_dot_unpacked_success_file_exists(version) {
+            return Ok(());
+        }
+        if self.releases.is_none() {
+            let cached = op::list_cached_go_releases()?;
+            if cached
+                .iter()
+                .any(|r| r.version == version.trim_start_matches("go"))
+            {
+                return goup_downloader::check_go_release(version, &cached);
+            }
+        }
+        match self.releases() {
+            Ok(releases) => goup_downloader::check_go_release(version, releases),
+            Err(e) => {
+                log::warn!("Validating {version} with the release index failed: {e}");
+                Ok(())
+            }
+        }
+    }
 }
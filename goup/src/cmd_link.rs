@@ -0,0 +1,9 @@
+use std::path::Path;
+
+use goup_misc::op;
+
+pub(super) fn run(name: &str, goroot: &Path) -> anyhow::Result<()> {
+    let version = op::link_go_version(name, goroot)?;
+    println!("Linked '{name}' to {} ({version})", goroot.display());
+    Ok(())
+}
@@ -1,10 +1,47 @@
 use colored::Colorize;
 use which::which;
 
-use goup_misc::op;
+use indicatif::HumanBytes;
 
-pub(super) fn run() -> anyhow::Result<()> {
+use goup_misc::{du, op};
+
+use crate::output::{self, ListItem, Output};
+
+pub(super) fn run(size: bool, output: Output) -> anyhow::Result<()> {
     let vers = op::list_go_version()?;
+    // size of the version directories, the caches are not measured.
+    let measure = size || output == Output::Json;
+    let size_of = |v: &goup_misc::Version| {
+        v.path
+            .parent()
+            .filter(|_| measure)
+            .and_then(|dir| du::dir_size(dir).ok())
+            .unwrap_or_default()
+    };
+    if output == Output::Json {
+        let items = vers
+            .into_iter()
+            .map(|v| {
+                let size = size_of(&v);
+                let linked = v.linked.is_some();
+                let (name, path) = match v.linked {
+                    Some(linked) => (linked.name, linked.goroot),
+                    None => (v.version.to_string(), v.path),
+                };
+                Ok(ListItem {
+                    version: v.version.to_string(),
+                    name,
+                    active: v.active,
+                    path,
+                    size,
+                    linked,
+                    aliases: v.aliases,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        return output::print_json(&items);
+    }
+
     if vers.is_empty() {
         println!("No Go is installed by goup.");
         if let Ok(go_bin) = which("go") {
@@ -15,10 +52,25 @@ pub(super) fn run() -> anyhow::Result<()> {
         colored::control::set_virtual_terminal(true).unwrap();
 
         for v in vers {
+            let mut name = match &v.linked {
+                Some(linked) => format!(
+                    "{} -> {} ({})",
+                    linked.name,
+                    linked.goroot.display(),
+                    v.version
+                ),
+                None => v.version.to_string(),
+            };
+            if !v.aliases.is_empty() {
+                name = format!("{name} [{}]", v.aliases.join(", "));
+            }
+            if size {
+                name = format!("{name} ({})", HumanBytes(size_of(&v)));
+            }
             if v.active {
-                println!("* {}", v.version.to_string().green());
+                println!("* {}", name.green());
             } else {
-                println!("  {}", v.version);
+                println!("  {name}");
             };
         }
     }
@@ -0,0 +1,59 @@
+use std::path::PathBuf;
+
+use clap::Subcommand;
+use indicatif::HumanBytes;
+
+use goup_misc::consts;
+
+#[derive(Subcommand, Debug, PartialEq)]
+pub(super) enum MirrorCommand {
+    /// Download the archives of the versions into a directory served by any static web server,
+    /// the archives already present and verified are skipped.
+    Sync {
+        /// the mirror directory, point `GOUP_GO_HOST` at its root and
+        /// `GOUP_GO_DOWNLOAD_BASE_URL` at its `dl`.
+        #[arg(long)]
+        dest: PathBuf,
+        /// the versions, exact or a requirement, such as '>=1.20', repeat for several.
+        #[arg(long, required = true)]
+        versions: Vec<String>,
+        /// the platforms, such as 'linux/amd64,darwin/arm64', the current platform if not given.
+        #[arg(long, value_delimiter = ',')]
+        platforms: Vec<String>,
+        /// mirror the beta and rc versions in the range too.
+        #[arg(long, default_value_t = false)]
+        include_unstable: bool,
+        /// host that is used to fetch the release index.
+        #[arg(long, default_value_t = consts::GO_HOST.to_owned(), env = consts::GOUP_GO_HOST)]
+        host: String,
+    },
+}
+
+pub(super) fn run(command: MirrorCommand) -> anyhow::Result<()> {
+    match command {
+        MirrorCommand::Sync {
+            dest,
+            versions,
+            platforms,
+            include_unstable,
+            host,
+        } => {
+            let r = goup_downloader::sync_mirror(
+                &dest,
+                &versions,
+                &platforms,
+                include_unstable,
+                &host,
+            )?;
+            println!(
+                "Mirrored {} version(s) into {}, {} archive(s) downloaded ({}), {} up to date",
+                r.versions.len(),
+                dest.display(),
+                r.downloaded,
+                HumanBytes(r.bytes),
+                r.skipped
+            );
+        }
+    }
+    Ok(())
+}
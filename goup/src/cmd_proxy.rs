@@ -0,0 +1,9 @@
+use goup_downloader::ToolchainProxy;
+
+pub(super) fn run(listen: &str, fetch: bool, sumdb: &str) -> anyhow::Result<()> {
+    let proxy = ToolchainProxy::bind(listen, fetch, sumdb)?;
+    let addr = proxy.local_addr();
+    println!("Serving golang.org/toolchain on http://{addr}, point the go command at it with:");
+    println!("  GOPROXY=http://{addr},https://proxy.golang.org,direct");
+    proxy.run()
+}
@@ -0,0 +1,29 @@
+use goup_misc::op;
+use goup_misc::prune::{self, KeepPolicy, Verdict};
+
+pub(super) fn run(policy: KeepPolicy, keep_active: bool, dry_run: bool) -> anyhow::Result<()> {
+    let versions = op::list_go_version()?
+        .into_iter()
+        .filter(|v| v.linked.is_none())
+        .filter_map(|v| {
+            let name = v.path.parent()?.file_name()?.to_string_lossy().to_string();
+            Some((name, v.version))
+        })
+        .collect();
+    let protected = prune::protected_go_versions(keep_active)?;
+
+    let mut removes = Vec::new();
+    for (name, verdict) in policy.plan(versions, &protected) {
+        match verdict {
+            Verdict::Keep(reason) => println!("Keep {name} ({reason})"),
+            Verdict::Remove => {
+                println!("{} {name}", if dry_run { "Would remove" } else { "Remove" });
+                removes.push(name);
+            }
+        }
+    }
+    if dry_run || removes.is_empty() {
+        return Ok(());
+    }
+    op::remove_go_versions(&removes)
+}
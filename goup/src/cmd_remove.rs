@@ -1,17 +1,53 @@
-use dialoguer::{MultiSelect, theme::ColorfulTheme};
+use dialoguer::{Confirm, MultiSelect, theme::ColorfulTheme};
 
+use goup_misc::Version;
 use goup_misc::op;
 
-pub(super) fn run(version: Vec<String>) -> anyhow::Result<()> {
+/// how to remove the active version.
+pub(super) struct ActiveRemoval {
+    /// skip the prompt.
+    pub yes: bool,
+    /// remove the active version without the prompt.
+    pub force: bool,
+    /// leave no active version instead of switching to the latest stable installed version.
+    pub no_switch: bool,
+}
+
+pub(super) fn run(version: Vec<String>, active: ActiveRemoval) -> anyhow::Result<()> {
     if !version.is_empty() {
-        return op::remove_go_versions(&version);
+        let mut vers = Vec::new();
+        for ver in &version {
+            let matched = op::match_installed_go_versions(ver)?;
+            if matched.is_empty() {
+                anyhow::bail!("No installed Go version matches '{ver}'.");
+            }
+            if matched.len() > 1 && !confirm_matched(ver, &matched, active.yes)? {
+                println!("Skipped the versions matching '{ver}'");
+                continue;
+            }
+            for v in matched {
+                if !vers.contains(&v) {
+                    vers.push(v);
+                }
+            }
+        }
+        if vers.is_empty() {
+            return Ok(());
+        }
+        return remove(vers, &active);
     }
 
     let vers = op::list_go_version()?;
     if vers.is_empty() {
         anyhow::bail!("No go is installed");
     }
-    let items: Vec<String> = vers.iter().map(|v| v.version.to_string()).collect();
+    let items: Vec<String> = vers
+        .iter()
+        .map(|v| match &v.linked {
+            Some(linked) => linked.name.clone(),
+            None => v.version.to_string(),
+        })
+        .collect();
     let selection = MultiSelect::with_theme(&ColorfulTheme::default())
         .with_prompt("Select multiple version")
         .items(&items)
@@ -21,5 +57,71 @@ pub(super) fn run(version: Vec<String>) -> anyhow::Result<()> {
     }
 
     let vers: Vec<String> = selection.into_iter().map(|i| items[i].clone()).collect();
+    remove(vers, &active)
+}
+
+/// a requirement matching several installed versions removes them all, list them first.
+fn confirm_matched(ver: &str, matched: &[String], yes: bool) -> anyhow::Result<bool> {
+    println!("'{ver}' matches {}: {}", matched.len(), matched.join(", "));
+    if yes {
+        return Ok(true);
+    }
+    Ok(Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("Remove all {} versions?", matched.len()))
+        .interact()?)
+}
+
+fn remove(mut vers: Vec<String>, active: &ActiveRemoval) -> anyhow::Result<()> {
+    let Some(current) = op::current_go_version()? else {
+        return op::remove_go_versions(&vers);
+    };
+    let resolved = vers
+        .iter()
+        .map(|v| Version::resolve(v))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    if !resolved.contains(&current) {
+        return op::remove_go_versions(&vers);
+    }
+
+    let fallback = if active.no_switch {
+        None
+    } else {
+        op::latest_stable_go_version(&resolved)?
+    };
+    let action = match &fallback {
+        Some(fallback) => format!("switch to {fallback}"),
+        None => "leave no active version".to_owned(),
+    };
+    let confirmation = if active.force {
+        true
+    } else if active.yes {
+        anyhow::bail!("{current} is the active version, add --force to remove it and {action}.");
+    } else {
+        Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!(
+                "{current} is the active version, remove it and {action}?"
+            ))
+            .interact()?
+    };
+    if confirmation {
+        match &fallback {
+            Some(fallback) => op::set_go_version(fallback)?,
+            None => {
+                op::unset_go_version()?;
+                println!("No Go is active now, set one with `goup set`.");
+            }
+        }
+    } else {
+        println!("Skipped the active version {current}");
+        vers = vers
+            .into_iter()
+            .zip(resolved)
+            .filter(|(_, r)| *r != current)
+            .map(|(v, _)| v)
+            .collect();
+        if vers.is_empty() {
+            return Ok(());
+        }
+    }
     op::remove_go_versions(&vers)
 }
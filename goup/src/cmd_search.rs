@@ -2,22 +2,38 @@ use std::collections::HashMap;
 
 use colored::Colorize;
 
-use goup_misc::op;
+use goup_misc::{Version, op};
 
-pub(super) fn run(filter: Option<String>, host: String) -> anyhow::Result<()> {
+use crate::output::{self, Output, SearchItem};
+
+pub(super) fn run(filter: Option<String>, host: String, output: Output) -> anyhow::Result<()> {
     let filter = filter.and_then(|s| s.parse().ok());
-    let remote_versions = op::list_upstream_go_versions_filter(&host, filter)?;
+    let remote_releases = op::list_upstream_go_releases_filter(&host, filter)?;
 
     let local_versions = op::list_go_version()?;
     let mut v_a_map = HashMap::<String, bool>::new();
-    for v in local_versions {
+    for v in local_versions.into_iter().filter(|v| v.linked.is_none()) {
         v_a_map.insert(v.version.to_string(), v.active);
     }
 
+    if output == Output::Json {
+        let items: Vec<_> = remote_releases
+            .iter()
+            .map(|r| SearchItem {
+                version: r.version.clone(),
+                channel: Version::channel(&r.version).to_owned(),
+                installed: v_a_map.contains_key(&r.version),
+                active: v_a_map.get(&r.version).copied().unwrap_or_default(),
+                platforms: r.platforms(),
+            })
+            .collect();
+        return output::print_json(&items);
+    }
+
     #[cfg(windows)]
     colored::control::set_virtual_terminal(true).unwrap();
 
-    for v in remote_versions {
+    for v in remote_releases.into_iter().map(|r| r.version) {
         if !v_a_map.contains_key(&v) {
             println!("  {v}");
             continue;
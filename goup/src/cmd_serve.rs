@@ -0,0 +1,14 @@
+use goup_downloader::MirrorServer;
+use goup_misc::Dir;
+
+pub(super) fn run(listen: &str, fetch: bool, host: String) -> anyhow::Result<()> {
+    let server = MirrorServer::bind(listen, fetch.then_some(host))?;
+    let addr = server.local_addr();
+    println!(
+        "Serving {} on http://{addr}, point other goup at it with:",
+        Dir::goup_home()?.cache().display()
+    );
+    println!("  GOUP_GO_HOST=http://{addr}");
+    println!("  GOUP_GO_DOWNLOAD_BASE_URL=http://{addr}/dl");
+    server.run()
+}
@@ -2,8 +2,14 @@ use dialoguer::{Select, theme::ColorfulTheme};
 
 use goup_misc::op;
 
-pub(super) fn run(version: Option<String>) -> anyhow::Result<()> {
+use crate::cmd_install;
+
+pub(super) fn run(version: Option<String>, install: bool, host: &str) -> anyhow::Result<()> {
     if let Some(version) = version {
+        if install && op::match_installed_go_versions(&version)?.is_empty() {
+            let version = cmd_install::install(version, host)?;
+            return op::set_go_version(&version);
+        }
         return op::set_go_version(&version);
     }
 
@@ -15,7 +21,10 @@ pub(super) fn run(version: Option<String>) -> anyhow::Result<()> {
     let mut items = vec![];
     let mut pos = 0;
     for (i, v) in vers.iter().enumerate() {
-        items.push(v.version.to_string());
+        items.push(match &v.linked {
+            Some(linked) => linked.name.clone(),
+            None => v.version.to_string(),
+        });
         if v.active {
             pos = i;
         }
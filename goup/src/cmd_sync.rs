@@ -0,0 +1,163 @@
+use std::path::Path;
+
+use goup_misc::op;
+use goup_misc::sync::{GoupToml, PinCheck, SyncPlan};
+use goup_misc::{GoVersionReq, consts};
+
+use crate::cmd_install::{self, Resolver};
+
+pub(super) fn run(
+    file: &Path,
+    host: Option<String>,
+    prune: bool,
+    check: bool,
+) -> anyhow::Result<()> {
+    let manifest = GoupToml::load(file)?;
+    let host = host
+        .or_else(|| manifest.mirror.host.clone())
+        .unwrap_or_else(|| consts::GO_HOST.to_owned());
+    if let Some(url) = &manifest.mirror.download_base_url {
+        consts::set_go_download_base_url(url);
+    }
+
+    let installed = op::list_managed_go_versions()?;
+    let mut resolver = Resolver::new(&host);
+    let required = manifest
+        .versions
+        .iter()
+        .map(|v| resolve(&mut resolver, &installed, v))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let default = manifest
+        .default
+        .as_deref()
+        .map(|v| resolve(&mut resolver, &installed, v))
+        .transpose()?;
+    let plan = SyncPlan::new(required, default, &installed);
+
+    if check {
+        return check_plan(file, &manifest, &plan, prune);
+    }
+
+    // the pinned sha256 is verified against the archive before unpacking it, the archive
+    // is downloaded again if it was pruned from the cache.
+    let mut mismatched = 0;
+    for version in &plan.required {
+        if let PinCheck::Mismatch { expected, actual } =
+            goup_downloader::verify_pinned_sha256(&manifest, version)?
+        {
+            mismatched += 1;
+            println!("{version}: sha256 {actual} does not match the pinned {expected}");
+        }
+    }
+    if mismatched > 0 {
+        anyhow::bail!("{mismatched} version(s) do not match the pinned sha256.");
+    }
+
+    if !plan.missing.is_empty() {
+        cmd_install::install_many(&plan.missing, &host)?;
+    }
+
+    let current = op::current_go_version()?;
+    if let Some(default) = &plan.default
+        && current.as_ref() != Some(default)
+    {
+        op::set_go_version(default)?;
+    }
+
+    if prune && !plan.extra.is_empty() {
+        let current = op::current_go_version()?;
+        let extra: Vec<_> = plan
+            .extra
+            .iter()
+            .filter(|v| {
+                let active = current.as_ref() == Some(*v);
+                if active {
+                    log::warn!("{v} is the active version, it is not removed.");
+                }
+                !active
+            })
+            .cloned()
+            .collect();
+        if !extra.is_empty() {
+            op::remove_go_versions(&extra)?;
+        }
+    }
+
+    println!(
+        "{} is in sync, {} version(s) required.",
+        file.display(),
+        plan.required.len()
+    );
+    Ok(())
+}
+
+/// report how the machine is out of sync with the manifest, fails if it is.
+fn check_plan(
+    file: &Path,
+    manifest: &GoupToml,
+    plan: &SyncPlan,
+    prune: bool,
+) -> anyhow::Result<()> {
+    let mut issues = Vec::new();
+    for version in &plan.required {
+        if plan.missing.contains(version) {
+            issues.push(format!("{version} is not installed"));
+            continue;
+        }
+        let archive = goup_downloader::go_version_archive(version);
+        match manifest.check_pinned_sha256(&archive)? {
+            PinCheck::Mismatch { expected, actual } => issues.push(format!(
+                "{version}: sha256 {actual} does not match the pinned {expected}"
+            )),
+            PinCheck::Unknown => issues.push(format!(
+                "{version}: {archive} is not cached, run `goup sync` to verify the pinned sha256"
+            )),
+            PinCheck::Unpinned | PinCheck::Match => {}
+        }
+    }
+    if let Some(default) = &plan.default {
+        let current = op::current_go_version()?;
+        if current.as_ref() != Some(default) {
+            issues.push(format!(
+                "the default version is {}, not {default}",
+                current.as_deref().unwrap_or("unset")
+            ));
+        }
+    }
+    if prune {
+        for version in &plan.extra {
+            issues.push(format!("{version} is not listed"));
+        }
+    }
+
+    for issue in &issues {
+        println!("{issue}");
+    }
+    if !issues.is_empty() {
+        anyhow::bail!(
+            "out of sync with {}, {} issue(s) found.",
+            file.display(),
+            issues.len()
+        );
+    }
+    println!("{} is in sync.", file.display());
+    Ok(())
+}
+
+/// the installed version which satisfies the requirement, so a machine in sync stays in
+/// sync when upstream ships a patch release. the missing ones and the channels, such as
+/// `oldstable`, are resolved with the upstream release index.
+fn resolve(resolver: &mut Resolver, installed: &[String], ver: &str) -> anyhow::Result<String> {
+    let channel = matches!(
+        ver.parse::<GoVersionReq>(),
+        Ok(GoVersionReq::Stable | GoVersionReq::OldStable | GoVersionReq::Next)
+    );
+    if !channel
+        && let Some(version) = op::match_installed_go_versions(ver)?
+            .into_iter()
+            .find(|v| installed.contains(v))
+    {
+        return Ok(version);
+    }
+    resolver.resolve(ver)
+}
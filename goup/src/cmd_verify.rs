@@ -0,0 +1,85 @@
+use colored::Colorize;
+
+use goup_misc::Version;
+use goup_misc::op;
+use goup_misc::progress;
+
+pub(super) fn run(version: Vec<String>, repair: bool) -> anyhow::Result<()> {
+    #[cfg(windows)]
+    colored::control::set_virtual_terminal(true).unwrap();
+
+    let vers = if version.is_empty() {
+        op::list_managed_go_versions()?
+    } else {
+        version
+    };
+    if vers.is_empty() {
+        anyhow::bail!("No go is installed");
+    }
+
+    let mut problems = 0;
+    let mut repairable = false;
+    for ver in &vers {
+        let sp = progress::spinner();
+        sp.set_message(format!("Verifying {ver}"));
+        let result = op::verify_go_version(ver);
+        sp.finish_and_clear();
+        match result {
+            Ok(Some(report)) => {
+                let intact = report.is_intact();
+                let status = if intact {
+                    "ok".green()
+                } else {
+                    "damaged".red()
+                };
+                println!(
+                    "{ver}: {status}, {} modified, {} missing, {} extra",
+                    report.modified.len(),
+                    report.missing.len(),
+                    report.extra.len()
+                );
+                for f in &report.modified {
+                    println!("  modified: {f}");
+                }
+                for f in &report.missing {
+                    println!("  missing: {f}");
+                }
+                for f in &report.extra {
+                    println!("  extra: {f}");
+                }
+                if intact {
+                    continue;
+                }
+            }
+            Ok(None) => {
+                // installed before manifests were recorded, nothing to compare with.
+                println!("{ver}: {}, no manifest recorded", "unknown".yellow());
+                continue;
+            }
+            Err(e) => {
+                println!("{ver}: {}, {e}", "error".red());
+                problems += 1;
+                continue;
+            }
+        }
+        if !repair {
+            problems += 1;
+            repairable = true;
+            continue;
+        }
+        match goup_downloader::repair_go_version(&Version::resolve(ver)?) {
+            Ok(()) => println!("  repaired from the cached archive"),
+            Err(e) => {
+                println!("  repair failed: {e}");
+                problems += 1;
+            }
+        }
+    }
+    if problems > 0 {
+        if repairable {
+            println!("Run `goup verify --repair` to reinstall from the cached archive.");
+        }
+        anyhow::bail!("{problems} version(s) failed verification.");
+    }
+    Ok(())
+}
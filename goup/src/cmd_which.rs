@@ -0,0 +1,20 @@
+use goup_misc::op::{self, ActiveSource};
+
+pub(super) fn run(tool: &str) -> anyhow::Result<()> {
+    // `${GOUP_HOME}/current/bin` on PATH runs the Go set by `goup set`, whatever
+    // GOUP_GO_VERSION or `.go-version` select.
+    let global = op::current_go_version()?
+        .ok_or_else(|| anyhow::anyhow!("No Go is set, set it with `goup set`."))?;
+    if let Some(active) = op::active_go_version().ok().flatten()
+        && active.source != ActiveSource::Global
+        && active.version != global
+    {
+        log::warn!(
+            "{} is selected, but not applied, the Go on PATH is {global}.",
+            active.version
+        );
+    }
+    let path = op::which_go_tool(&global, tool)?;
+    println!("{}", path.display());
+    Ok(())
+}
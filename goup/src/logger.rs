@@ -0,0 +1,130 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use colored::Colorize;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+use goup_misc::{consts, progress};
+
+/// a logger prints records to stderr above the progress bars,
+/// and optionally writes debug records to a log file.
+struct Logger {
+    level: LevelFilter,
+    file: Option<Mutex<File>>,
+}
+
+impl Logger {
+    fn file_level(&self) -> LevelFilter {
+        if self.file.is_some() {
+            LevelFilter::Debug
+        } else {
+            LevelFilter::Off
+        }
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        let level = self.level.max(self.file_level());
+        // records of dependencies are too noisy, only shown in trace level.
+        metadata.level() <= level
+            && (metadata.target().starts_with("goup") || level == LevelFilter::Trace)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        if record.level() <= self.level {
+            let level = match record.level() {
+                Level::Error => "error".red(),
+                Level::Warn => "warn".yellow(),
+                Level::Info => "info".green(),
+                Level::Debug => "debug".blue(),
+                Level::Trace => "trace".dimmed(),
+            };
+            progress::multi_progress().suspend(|| eprintln!("{level}: {}", record.args()));
+        }
+        if record.level() <= self.file_level()
+            && let Some(file) = &self.file
+            && let Ok(mut file) = file.lock()
+        {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default();
+            let _ = writeln!(
+                file,
+                "{}.{:03} {:<5} {}: {}",
+                now.as_secs(),
+                now.subsec_millis(),
+                record.level(),
+                record.target(),
+                record.args()
+            );
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(file) = &self.file
+            && let Ok(mut file) = file.lock()
+        {
+            let _ = file.flush();
+        }
+    }
+}
+
+/// the console log level, `-q` and `-v` take precedence over `GOUP_LOG`.
+fn level(verbose: u8, quiet: bool, env_level: Option<&str>) -> LevelFilter {
+    if quiet {
+        return LevelFilter::Error;
+    }
+    match verbose {
+        0 => env_level
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(LevelFilter::Warn),
+        1 => LevelFilter::Info,
+        2 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    }
+}
+
+/// install the logger, progress bars are hidden in quiet mode.
+pub(crate) fn init(verbose: u8, quiet: bool, log_file: Option<&Path>) -> anyhow::Result<()> {
+    let env_level = std::env::var(consts::GOUP_LOG).ok();
+    let file = log_file
+        .map(|p| OpenOptions::new().create(true).append(true).open(p))
+        .transpose()?
+        .map(Mutex::new);
+    let logger = Logger {
+        level: level(verbose, quiet, env_level.as_deref()),
+        file,
+    };
+    log::set_max_level(logger.level.max(logger.file_level()));
+    log::set_logger(Box::leak(Box::new(logger))).map_err(|e| anyhow::anyhow!("{e}"))?;
+    if quiet {
+        progress::hide();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level() {
+        assert_eq!(level(0, false, None), LevelFilter::Warn);
+        assert_eq!(level(1, false, None), LevelFilter::Info);
+        assert_eq!(level(2, false, None), LevelFilter::Debug);
+        assert_eq!(level(5, false, None), LevelFilter::Trace);
+        assert_eq!(level(0, true, None), LevelFilter::Error);
+        assert_eq!(level(0, false, Some("debug")), LevelFilter::Debug);
+        assert_eq!(level(0, false, Some("off")), LevelFilter::Off);
+        assert_eq!(level(0, false, Some("bogus")), LevelFilter::Warn);
+        assert_eq!(level(1, false, Some("error")), LevelFilter::Info);
+        assert_eq!(level(0, true, Some("debug")), LevelFilter::Error);
+    }
+}
@@ -1,15 +1,38 @@
+mod cmd_alias;
+mod cmd_bundle;
+mod cmd_cache;
 mod cmd_clean;
+mod cmd_completions;
+mod cmd_current;
+mod cmd_dedupe;
+mod cmd_doctor;
+mod cmd_du;
 mod cmd_env;
 mod cmd_install;
+mod cmd_link;
 mod cmd_list;
+mod cmd_mirror;
+mod cmd_proxy;
+mod cmd_prune;
 mod cmd_remove;
 mod cmd_search;
+mod cmd_serve;
 mod cmd_set;
+mod cmd_sync;
+mod cmd_verify;
+mod cmd_which;
+mod logger;
+mod output;
 
-use clap::Parser;
+use clap::{ArgAction, CommandFactory, Parser, Subcommand};
+use clap_complete::{ArgValueCandidates, CompleteEnv, Shell};
 use goup_misc::consts;
+use goup_misc::prune::KeepPolicy;
 use shadow_rs::shadow;
 use std::env::consts::{ARCH, OS};
+use std::path::PathBuf;
+
+use output::Output;
 
 shadow!(build);
 // rust-analyzer: attemping to use nonexistent positional argument `12`
@@ -49,29 +72,74 @@ BuildArch:       {}"#,
 #[command(propagate_version = true)]
 #[command(version = VERSION)]
 #[command(name = "goup")]
+struct Cli {
+    /// output format, 'json' is supported by list, search, env, current, du and cache list.
+    #[arg(long, global = true, value_enum, default_value_t = Output::Text)]
+    output: Output,
+    /// increase log verbosity, '-v' for info, '-vv' for debug and '-vvv' for trace.
+    /// GOUP_LOG environment variable sets the log level(error, warn, info, debug, trace) too.
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    verbose: u8,
+    /// only print errors, and hide progress bars.
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// also write debug logs to the file, for troubleshooting reports.
+    #[arg(long, global = true, value_name = "FILE")]
+    log_file: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug, PartialEq)]
 #[non_exhaustive] // 表明未来还有其它元素添加
-enum Cli {
+enum Command {
     /// Install Go with a version
     #[command(visible_aliases = ["i", "add"])]
     Install {
-        /// toolchain name, such as 'stable', 'nightly'('tip', 'gotip'), 'unstable', 'beta' or '=1.21.4'
-        #[arg(default_value = "stable")]
-        toolchain: String,
+        /// toolchain name, such as 'stable', 'nightly'('tip', 'gotip'), 'unstable', 'beta',
+        /// or a version requirement, such as '1.21.4', '1.21'(newest 1.21.x), '~1.21', '>=1.20, <1.22',
+        /// 'latest', 'oldstable' or 'next'(newest pre-release newer than stable).
+        /// several toolchains are installed concurrently.
+        #[arg(default_value = "stable", add = ArgValueCandidates::new(cmd_completions::upstream_versions))]
+        toolchain: Vec<String>,
         /// host that is used to download Go.
         #[arg(long, default_value_t = consts::GO_HOST.to_owned(), env = consts::GOUP_GO_HOST)]
         host: String,
+        /// hard link the files identical to the other installed versions, see `goup dedupe`.
+        #[arg(long, default_value_t = false)]
+        dedupe: bool,
+        /// set the installed version as the default Go, see `goup set`.
+        #[arg(long = "use", default_value_t = false)]
+        use_version: bool,
     },
 
     /// List all installed Go
     #[command(visible_aliases = ["ls", "show"])]
-    List,
+    List {
+        /// show the disk usage of each version, see `goup du`.
+        #[arg(long, default_value_t = false)]
+        size: bool,
+    },
 
     /// Remove the specified Go version list.
     /// If no version is provided, a prompt will show to select multiple installed Go version.
     #[command(visible_alias = "rm")]
     Remove {
-        /// target go version list.
+        /// target go version list, a requirement removes every installed version it matches,
+        /// such as '<1.20' or '1.21', after a prompt if it matches several.
+        #[arg(add = ArgValueCandidates::new(cmd_completions::installed_versions))]
         version: Vec<String>,
+        /// skip the prompt, the active version is refused unless --force is given.
+        #[arg(short, long, default_value_t = false)]
+        yes: bool,
+        /// remove the active version without the prompt.
+        #[arg(long, default_value_t = false)]
+        force: bool,
+        /// leave no active version instead of switching to the latest stable installed version
+        /// when the active version is removed.
+        #[arg(long, default_value_t = false)]
+        no_switch: bool,
     },
 
     /// Search Go versions to install
@@ -88,13 +156,189 @@ enum Cli {
     /// If no version is provided, a prompt will show to select a installed Go version.
     #[command(visible_alias = "use")]
     Set {
-        /// the version to set.
+        /// the version to set, a requirement sets the newest installed version it matches,
+        /// such as '1.21' or '^1.21'.
+        #[arg(add = ArgValueCandidates::new(cmd_completions::installed_versions))]
         version: Option<String>,
+        /// install the version first if it is not installed, a requirement installs the newest
+        /// upstream version matching it if no installed version does.
+        #[arg(long, visible_alias = "remote", default_value_t = false)]
+        install: bool,
+        /// host that is used to download Go.
+        #[arg(long, default_value_t = consts::GO_HOST.to_owned(), env = consts::GOUP_GO_HOST)]
+        host: String,
+    },
+
+    /// Show the active Go version and why it is selected.
+    /// GOUP_GO_VERSION environment variable takes precedence over the nearest '.go-version' file,
+    /// which takes precedence over `goup set`. Only `goup set` switches the Go on PATH,
+    /// a selection by the others is shown as not applied.
+    Current,
+
+    /// Show the absolute path of a tool of the Go on PATH, which is set by `goup set`.
+    Which {
+        /// tool name, such as 'go', 'gofmt' or 'vet'.
+        #[arg(default_value = "go")]
+        tool: String,
+    },
+
+    /// Link an externally installed Go (distro package, Homebrew, custom build) as a named toolchain.
+    /// Removing it later only unlinks it, the GOROOT is left untouched.
+    Link {
+        /// toolchain name, such as 'system', then use it with `goup set system`.
+        name: String,
+        /// path to the GOROOT of the Go installation, such as '/usr/lib/go'.
+        goroot: PathBuf,
+    },
+
+    /// Manage named aliases of Go versions, such as 'prod' -> '1.21.9'.
+    /// An alias can be used anywhere a version is expected, such as `goup set prod`.
+    /// If no name is provided, all aliases are listed.
+    Alias {
+        /// alias name, such as 'prod'.
+        #[arg(add = ArgValueCandidates::new(cmd_completions::aliases))]
+        name: Option<String>,
+        /// the version the alias targets, such as '1.21.9' or '1.23rc2'.
+        #[arg(add = ArgValueCandidates::new(cmd_completions::installed_versions))]
+        target: Option<String>,
+        /// delete the alias.
+        #[arg(
+            short,
+            long,
+            default_value_t = false,
+            requires = "name",
+            conflicts_with = "target"
+        )]
+        delete: bool,
     },
 
     /// Show the specified goup environment variables and values.
     Env,
 
+    /// Diagnose the goup environment, such as PATH, GOROOT and the `current` link.
+    Doctor {
+        /// apply safe repairs, such as removing a dangling link or interrupted downloads.
+        #[arg(long, default_value_t = false)]
+        fix: bool,
+    },
+
+    /// Generate the shell completion script, versions are completed dynamically.
+    /// Such as `source <(goup completions bash)` in '~/.bashrc'.
+    Completions {
+        /// the shell to generate the script for.
+        shell: Shell,
+    },
+
+    /// Verify the installed files of Go versions against the manifest recorded at install time.
+    /// If no version is provided, all installed Go versions are verified.
+    Verify {
+        /// target go version list.
+        #[arg(add = ArgValueCandidates::new(cmd_completions::installed_versions))]
+        target: Vec<String>,
+        /// reinstall the damaged version from the cached archive.
+        #[arg(long, default_value_t = false)]
+        repair: bool,
+    },
+
+    /// Remove old Go versions, keeping the newest ones.
+    /// The active version, alias targets and versions pinned by `.go-version` files
+    /// in the GOUP_WORKSPACES roots are never removed.
+    Prune {
+        /// keep the newest N versions.
+        #[arg(long)]
+        keep: usize,
+        /// also keep the newest N versions of each minor release, such as 1.21.x.
+        #[arg(long, default_value_t = 0)]
+        per_minor: usize,
+        /// also keep the version active in the working directory, by GOUP_GO_VERSION or `.go-version`.
+        #[arg(long, default_value_t = false)]
+        keep_active: bool,
+        /// only print the versions to remove.
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+
+    /// Hard link the identical files across installed Go versions to save disk space.
+    /// Only supported on unix.
+    Dedupe {
+        /// copy back the hard linked files of the versions, all versions if none is given.
+        #[arg(long, num_args = 0.., value_name = "VERSION")]
+        undo: Option<Vec<String>>,
+        /// only report the space which would be saved.
+        #[arg(long, default_value_t = false, conflicts_with = "undo")]
+        dry_run: bool,
+    },
+
+    /// Install the versions listed in a `goup.toml` manifest, verify their pinned sha256
+    /// and set the default version.
+    Sync {
+        /// the manifest file.
+        #[arg(short, long, default_value = goup_misc::sync::GOUP_TOML)]
+        file: PathBuf,
+        /// host that is used to download Go, the mirror of the manifest is used if not given.
+        #[arg(long, env = consts::GOUP_GO_HOST)]
+        host: Option<String>,
+        /// remove the installed versions which are not listed.
+        #[arg(long, default_value_t = false)]
+        prune: bool,
+        /// only check, fails if the machine is out of sync.
+        #[arg(long, default_value_t = false)]
+        check: bool,
+    },
+
+    /// Show the disk usage of the installed Go versions and the caches.
+    Du {
+        /// sort the versions and caches by.
+        #[arg(long, value_enum, default_value_t = cmd_du::SortBy::Name)]
+        sort: cmd_du::SortBy,
+    },
+
+    /// Manage the downloaded archives.
+    Cache {
+        #[command(subcommand)]
+        command: cmd_cache::CacheCommand,
+    },
+
+    /// Pack and import the archives for offline machines.
+    Bundle {
+        #[command(subcommand)]
+        command: cmd_bundle::BundleCommand,
+    },
+
+    /// Build a static Go download mirror.
+    Mirror {
+        #[command(subcommand)]
+        command: cmd_mirror::MirrorCommand,
+    },
+
+    /// Serve the cached release index and archives as a Go download mirror for other goup.
+    Serve {
+        /// address to listen on.
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        listen: String,
+        /// download the release index from the host and the archives from
+        /// `GOUP_GO_DOWNLOAD_BASE_URL` on a cache miss.
+        #[arg(long, default_value_t = false)]
+        fetch: bool,
+        /// host that the release index is fetched through, the archives are not.
+        #[arg(long, default_value_t = consts::GO_HOST.to_owned(), env = consts::GOUP_GO_HOST)]
+        host: String,
+    },
+
+    /// Serve the cached and installed toolchains as the golang.org/toolchain module to the
+    /// toolchain switching of the go command.
+    Proxy {
+        /// address to listen on.
+        #[arg(long, default_value = "127.0.0.1:8081")]
+        listen: String,
+        /// download the archives from `GOUP_GO_DOWNLOAD_BASE_URL` on a cache miss.
+        #[arg(long, default_value_t = false)]
+        fetch: bool,
+        /// checksum database that `/sumdb/sum.golang.org` is forwarded to.
+        #[arg(long, default_value_t = goup_downloader::SUMDB_URL.to_owned())]
+        sumdb: String,
+    },
+
     /// Clean download archive file
     Clean {
         /// Skip interact prompt.
@@ -104,15 +348,79 @@ enum Cli {
 }
 
 fn main() -> anyhow::Result<()> {
+    CompleteEnv::with_factory(Cli::command)
+        .var(cmd_completions::COMPLETE)
+        .complete();
     let cli = Cli::parse();
-    use Cli::*;
-    match cli {
-        Install { toolchain, host } => cmd_install::run(toolchain, &host),
-        List => cmd_list::run(),
-        Remove { version } => cmd_remove::run(version),
-        Search { filter, host } => cmd_search::run(filter, host),
-        Set { version } => cmd_set::run(version),
-        Env => cmd_env::run(),
+    logger::init(cli.verbose, cli.quiet, cli.log_file.as_deref())?;
+    let output = cli.output;
+    use Command::*;
+    match cli.command {
+        Install {
+            toolchain,
+            host,
+            dedupe,
+            use_version,
+        } => cmd_install::run(toolchain, &host, dedupe, use_version),
+        List { size } => cmd_list::run(size, output),
+        Remove {
+            version,
+            yes,
+            force,
+            no_switch,
+        } => cmd_remove::run(
+            version,
+            cmd_remove::ActiveRemoval {
+                yes,
+                force,
+                no_switch,
+            },
+        ),
+        Search { filter, host } => cmd_search::run(filter, host, output),
+        Set {
+            version,
+            install,
+            host,
+        } => cmd_set::run(version, install, &host),
+        Current => cmd_current::run(output),
+        Which { tool } => cmd_which::run(&tool),
+        Link { name, goroot } => cmd_link::run(&name, &goroot),
+        Alias {
+            name,
+            target,
+            delete,
+        } => cmd_alias::run(name, target, delete),
+        Env => cmd_env::run(output),
+        Doctor { fix } => cmd_doctor::run(fix),
+        Completions { shell } => cmd_completions::run(shell),
+        Verify { target, repair } => cmd_verify::run(target, repair),
+        Prune {
+            keep,
+            per_minor,
+            keep_active,
+            dry_run,
+        } => cmd_prune::run(KeepPolicy { keep, per_minor }, keep_active, dry_run),
+        Sync {
+            file,
+            host,
+            prune,
+            check,
+        } => cmd_sync::run(&file, host, prune, check),
+        Du { sort } => cmd_du::run(sort, output),
+        Dedupe { undo, dry_run } => cmd_dedupe::run(undo, dry_run),
+        Cache { command } => cmd_cache::run(command, output),
+        Bundle { command } => cmd_bundle::run(command),
+        Mirror { command } => cmd_mirror::run(command),
+        Serve {
+            listen,
+            fetch,
+            host,
+        } => cmd_serve::run(&listen, fetch, host),
+        Proxy {
+            listen,
+            fetch,
+            sumdb,
+        } => cmd_proxy::run(&listen, fetch, &sumdb),
         Clean { yes } => cmd_clean::run(yes),
     }
 }
@@ -0,0 +1,244 @@
+use std::path::PathBuf;
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+/// output format of the commands.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Output {
+    /// human readable text.
+    #[default]
+    Text,
+    /// machine readable JSON.
+    Json,
+}
+
+/// print the value as pretty JSON to stdout.
+pub(crate) fn print_json<T: Serialize>(value: &T) -> anyhow::Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+/// an installed Go of `goup list`.
+#[derive(Debug, Serialize)]
+pub(crate) struct ListItem {
+    /// Go version: 1.21.5
+    pub version: String,
+    /// name used by `goup set` or `goup remove`: 1.21.5, system
+    pub name: String,
+    pub active: bool,
+    /// GOROOT of the Go.
+    pub path: PathBuf,
+    /// install size in bytes.
+    pub size: u64,
+    /// linked by `goup link` or not.
+    pub linked: bool,
+    pub aliases: Vec<String>,
+}
+
+/// an upstream Go of `goup search`.
+#[derive(Debug, Serialize)]
+pub(crate) struct SearchItem {
+    /// Go version: 1.21.5
+    pub version: String,
+    /// stable, unstable or beta.
+    pub channel: String,
+    pub installed: bool,
+    pub active: bool,
+    /// platforms have a binary archive: linux/amd64
+    pub platforms: Vec<String>,
+}
+
+/// the active Go of `goup current`.
+#[derive(Debug, Serialize)]
+pub(crate) struct CurrentItem {
+    /// Go version: 1.21.5
+    pub version: String,
+    /// env, file or global.
+    pub source: String,
+    /// the `.go-version` file if the source is file.
+    pub origin: Option<PathBuf>,
+    /// GOROOT of the Go.
+    pub path: PathBuf,
+    /// whether the Go on PATH is this one, only `goup set` switches it.
+    pub applied: bool,
+    /// Go version on PATH set by `goup set`: 1.21.5
+    pub global: Option<String>,
+}
+
+/// an environment variable of `goup env`.
+#[derive(Debug, Serialize)]
+pub(crate) struct EnvItem {
+    pub key: String,
+    pub value: String,
+    /// env or default.
+    pub source: String,
+}
+
+/// an archive of `goup cache list`.
+#[derive(Debug, Serialize)]
+pub(crate) struct CacheItem {
+    /// archive filename: go1.21.5.linux-amd64.tar.gz
+    pub archive: String,
+    /// Go version: 1.21.5
+    pub version: String,
+    /// size in bytes of the archive and its checksum.
+    pub size: u64,
+    /// seconds since the archive was downloaded.
+    pub age: u64,
+    pub installed: bool,
+}
+
+/// a version directory or a cache of `goup du`.
+#[derive(Debug, Serialize)]
+pub(crate) struct DuItem {
+    /// go1.21.5, cache or shared-cache
+    pub name: String,
+    /// version, linked, cache or shared-cache.
+    pub kind: String,
+    pub path: PathBuf,
+    /// size in bytes.
+    pub size: u64,
+    /// bytes hard linked with an item measured before.
+    pub shared: u64,
+}
+
+/// the report of `goup du`.
+#[derive(Debug, Serialize)]
+pub(crate) struct DuReport {
+    pub items: Vec<DuItem>,
+    /// sum of the sizes in bytes.
+    pub total: u64,
+    /// bytes actually used, hard linked files are counted once.
+    pub disk: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_list_item_schema() {
+        let item = ListItem {
+            version: "1.21.5".to_owned(),
+            name: "1.21.5".to_owned(),
+            active: true,
+            path: PathBuf::from("/home/dev/.goup/go1.21.5/go"),
+            size: 1024,
+            linked: false,
+            aliases: vec!["prod".to_owned()],
+        };
+        assert_eq!(
+            serde_json::to_value(item).unwrap(),
+            json!({
+                "version": "1.21.5",
+                "name": "1.21.5",
+                "active": true,
+                "path": "/home/dev/.goup/go1.21.5/go",
+                "size": 1024,
+                "linked": false,
+                "aliases": ["prod"],
+            })
+        );
+    }
+
+    #[test]
+    fn test_search_item_schema() {
+        let item = SearchItem {
+            version: "1.22rc1".to_owned(),
+            channel: "unstable".to_owned(),
+            installed: false,
+            active: false,
+            platforms: vec!["linux/amd64".to_owned(), "windows/amd64".to_owned()],
+        };
+        assert_eq!(
+            serde_json::to_value(item).unwrap(),
+            json!({
+                "version": "1.22rc1",
+                "channel": "unstable",
+                "installed": false,
+                "active": false,
+                "platforms": ["linux/amd64", "windows/amd64"],
+            })
+        );
+    }
+
+    #[test]
+    fn test_current_item_schema() {
+        let item = CurrentItem {
+            version: "1.21.5".to_owned(),
+            source: "file".to_owned(),
+            origin: Some(PathBuf::from("/home/dev/project/.go-version")),
+            path: PathBuf::from("/home/dev/.goup/go1.21.5/go"),
+            applied: false,
+            global: Some("1.22.0".to_owned()),
+        };
+        assert_eq!(
+            serde_json::to_value(item).unwrap(),
+            json!({
+                "version": "1.21.5",
+                "source": "file",
+                "origin": "/home/dev/project/.go-version",
+                "path": "/home/dev/.goup/go1.21.5/go",
+                "applied": false,
+                "global": "1.22.0",
+            })
+        );
+        let item = CurrentItem {
+            version: "1.21.5".to_owned(),
+            source: "global".to_owned(),
+            origin: None,
+            path: PathBuf::from("/home/dev/.goup/go1.21.5/go"),
+            applied: true,
+            global: Some("1.21.5".to_owned()),
+        };
+        assert_eq!(serde_json::to_value(item).unwrap()["origin"], json!(null));
+    }
+
+    #[test]
+    fn test_env_item_schema() {
+        let item = EnvItem {
+            key: "GOUP_GO_HOST".to_owned(),
+            value: "https://golang.google.cn".to_owned(),
+            source: "default".to_owned(),
+        };
+        assert_eq!(
+            serde_json::to_value(item).unwrap(),
+            json!({
+                "key": "GOUP_GO_HOST",
+                "value": "https://golang.google.cn",
+                "source": "default",
+            })
+        );
+    }
+
+    #[test]
+    fn test_du_report_schema() {
+        let report = DuReport {
+            items: vec![DuItem {
+                name: "go1.21.5".to_owned(),
+                kind: "version".to_owned(),
+                path: PathBuf::from("/home/dev/.goup/go1.21.5"),
+                size: 1024,
+                shared: 0,
+            }],
+            total: 1024,
+            disk: 1024,
+        };
+        assert_eq!(
+            serde_json::to_value(report).unwrap(),
+            json!({
+                "items": [{
+                    "name": "go1.21.5",
+                    "kind": "version",
+                    "path": "/home/dev/.goup/go1.21.5",
+                    "size": 1024,
+                    "shared": 0,
+                }],
+                "total": 1024,
+                "disk": 1024,
+            })
+        );
+    }
+}